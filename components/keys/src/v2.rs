@@ -5,6 +5,9 @@ use std::fmt::{self, Debug, Display};
 use std::hash::Hash;
 use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+use std::slice;
+use std::sync::Arc;
 
 use super::types::Key;
 use codec::byte::MemComparableByteCodec;
@@ -15,9 +18,55 @@ pub trait KeyLike:
 {
 }
 
+/// Codec for the timestamp / commit-version suffix appended to physical keys.
+///
+/// Historically every key ended in a fixed 8-byte big-endian-descending `u64` MVCC version,
+/// which was hard-coded throughout this module. Making the suffix a pluggable codec lets
+/// variable-width or non-MVCC key layouts (e.g. keys with no version suffix, or a future
+/// wider logical-clock encoding) be used without touching every slice conversion: the
+/// `PhysicalKey` / `PhysicalKeySlice` helpers (`append_ts`, `shrink_ts`, `get_ts`,
+/// `as_physical_slice_without_ts`) derive their suffix length from [`SUFFIX_LEN`].
+/// `LogicalKeySlice::as_physical_slice_without_ts` and `LogicalKeySlice::get_ts` (and the
+/// `as_logical_slice_without_ts` helpers, which go through them) still assume an 8-byte
+/// descending `u64` suffix.
+///
+/// [`SUFFIX_LEN`]: TimestampCodec::SUFFIX_LEN
+pub trait TimestampCodec: Send + Sync + 'static {
+    /// Number of trailing bytes the encoded timestamp occupies.
+    const SUFFIX_LEN: usize;
+
+    /// Encode `ts` into its `SUFFIX_LEN`-byte suffix representation.
+    fn encode(ts: u64) -> Vec<u8>;
+
+    /// Decode a timestamp from the trailing `SUFFIX_LEN` bytes of a key.
+    fn decode(suffix: &[u8]) -> u64;
+}
+
+/// The default MVCC timestamp codec: an 8-byte big-endian *descending* `u64`, so that newer
+/// versions (larger timestamps) sort before older ones within the same user key.
+pub struct MvccTsCodec;
+
+impl TimestampCodec for MvccTsCodec {
+    const SUFFIX_LEN: usize = 8;
+
+    #[inline]
+    fn encode(ts: u64) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(Self::SUFFIX_LEN);
+        buf.write_u64_desc(ts).unwrap();
+        buf
+    }
+
+    #[inline]
+    fn decode(mut suffix: &[u8]) -> u64 {
+        suffix.read_u64_desc().unwrap()
+    }
+}
+
 pub trait PhysicalKey: Sized + Clone + KeyLike + NumberEncoder + BufferWriter + Deref {
     const PHYSICAL_PREFIX: &'static [u8];
     type Slice: PhysicalKeySlice<OwnedKey = Self> + ?Sized;
+    /// Codec of the trailing timestamp suffix. Defaults to [`MvccTsCodec`] for the basic key.
+    type TsCodec: TimestampCodec;
 
     /// Only used for `PhysicalKey` implementations. Not intended to be used elsewhere.
     #[doc(hidden)]
@@ -40,6 +89,13 @@ pub trait PhysicalKey: Sized + Clone + KeyLike + NumberEncoder + BufferWriter +
         self._into_vec()
     }
 
+    /// Move the buffer into a reference-counted [`SharedPhysicalKey`], so that downstream
+    /// executor stages clone an atomic refcount instead of the bytes.
+    #[inline(never)]
+    fn to_shared(self) -> SharedPhysicalKey {
+        SharedPhysicalKey::from(self.into_physical_vec())
+    }
+
     #[inline(never)]
     fn as_physical_std_slice(&self) -> &[u8] {
         self._vec_ref().as_slice()
@@ -157,17 +213,15 @@ pub trait PhysicalKey: Sized + Clone + KeyLike + NumberEncoder + BufferWriter +
         self.as_logical_slice().len()
     }
 
-    // FIXME: This is a MVCC knowledge.
     #[inline(never)]
     fn append_ts(&mut self, ts: u64) {
-        self.write_u64_desc(ts).unwrap();
+        self.write_bytes(&Self::TsCodec::encode(ts)).unwrap();
     }
 
-    // FIXME: This is a MVCC knowledge.
     #[inline(never)]
     fn shrink_ts(&mut self) {
         let len = self._vec_ref().len();
-        self._vec_mut().truncate(len - 8);
+        self._vec_mut().truncate(len - Self::TsCodec::SUFFIX_LEN);
     }
 
     #[inline(never)]
@@ -235,6 +289,8 @@ impl<'a, Key: PhysicalKey> Drop for PhysicalKeyTsGuard<'a, Key> {
 
 pub trait PhysicalKeySlice: KeyLike + ToPhysicalKeySlice<Self> {
     type OwnedKey: PhysicalKey<Slice = Self>;
+    /// Codec of the trailing timestamp suffix. Must match the owning key's `TsCodec`.
+    type TsCodec: TimestampCodec;
 
     // TODO: Only to support `impl Key for ToPhysicalKeySlice<T>`. To be removed.
     type LegacyKeySliceOwner;
@@ -261,7 +317,7 @@ pub trait PhysicalKeySlice: KeyLike + ToPhysicalKeySlice<Self> {
     #[inline(never)]
     fn as_physical_slice_without_ts(&self) -> &Self {
         let s = self.as_physical_std_slice();
-        Self::from_physical_std_slice(&s[..s.len() - 8])
+        Self::from_physical_std_slice(&s[..s.len() - Self::TsCodec::SUFFIX_LEN])
     }
 
     #[inline(never)]
@@ -281,7 +337,8 @@ pub trait PhysicalKeySlice: KeyLike + ToPhysicalKeySlice<Self> {
 
     #[inline(never)]
     fn get_ts(&self) -> u64 {
-        self.as_logical_slice().get_ts()
+        let s = self.as_logical_std_slice();
+        Self::TsCodec::decode(&s[s.len() - Self::TsCodec::SUFFIX_LEN..])
     }
 }
 
@@ -337,6 +394,7 @@ impl Deref for BasicPhysicalKey {
 impl PhysicalKey for BasicPhysicalKey {
     const PHYSICAL_PREFIX: &'static [u8] = b"";
     type Slice = BasicPhysicalKeySlice;
+    type TsCodec = MvccTsCodec;
 
     #[inline(never)]
     fn _new_from_vec(vec: Vec<u8>) -> Self {
@@ -388,6 +446,7 @@ impl KeyLike for BasicPhysicalKeySlice {}
 
 impl PhysicalKeySlice for BasicPhysicalKeySlice {
     type OwnedKey = BasicPhysicalKey;
+    type TsCodec = MvccTsCodec;
 
     // TODO: Only to support `impl Key for ToPhysicalKeySlice<T>`. To be removed.
     type LegacyKeySliceOwner = ();
@@ -415,6 +474,287 @@ impl PhysicalKeySlice for BasicPhysicalKeySlice {
     }
 }
 
+/// A memory-compact, immutable physical key backed by a boxed slice.
+///
+/// `BasicPhysicalKey(Vec<u8>)` carries a 24-byte header (ptr/len/cap), but once a key is
+/// built it is never resized, so the capacity word is dead weight across the millions of
+/// keys a scanner holds live. `BoxedPhysicalKey` stores only `NonNull<u8>` + length,
+/// dropping the capacity field and saving 8 bytes per key.
+///
+/// Because the `PhysicalKey` trait is `Vec`-centric (it requires `_vec_mut` and
+/// `BufferWriter`), the mutable building phase is factored onto [`BasicPhysicalKey`]; a
+/// finished key is finalized into the compact read-only form here. `From<Vec<u8>>` /
+/// [`BoxedPhysicalKey::into_vec`] round-trip with the `Vec`-backed form.
+pub struct BoxedPhysicalKey {
+    ptr: NonNull<u8>,
+    len: usize,
+}
+
+unsafe impl Send for BoxedPhysicalKey {}
+unsafe impl Sync for BoxedPhysicalKey {}
+
+impl BoxedPhysicalKey {
+    #[inline]
+    fn as_std_slice(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    /// The same read-only physical slice view as [`BasicPhysicalKey::as_physical_slice`].
+    #[inline]
+    pub fn as_physical_slice(&self) -> &BasicPhysicalKeySlice {
+        BasicPhysicalKeySlice::from_physical_std_slice(self.as_std_slice())
+    }
+
+    #[inline]
+    pub fn as_physical_std_slice(&self) -> &[u8] {
+        self.as_std_slice()
+    }
+
+    #[inline]
+    pub fn as_logical_slice(&self) -> &LogicalKeySlice {
+        self.as_physical_slice().as_logical_slice()
+    }
+
+    #[inline]
+    pub fn get_ts(&self) -> u64 {
+        self.as_physical_slice().get_ts()
+    }
+
+    /// Round-trip back to the `Vec`-backed owned form.
+    #[inline]
+    pub fn into_vec(self) -> Vec<u8> {
+        let boxed = unsafe {
+            Box::from_raw(slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len))
+        };
+        // Prevent `Drop` from freeing the slice a second time.
+        std::mem::forget(self);
+        boxed.into_vec()
+    }
+}
+
+impl From<Vec<u8>> for BoxedPhysicalKey {
+    #[inline]
+    fn from(vec: Vec<u8>) -> BoxedPhysicalKey {
+        let len = vec.len();
+        let boxed = vec.into_boxed_slice();
+        let ptr = Box::into_raw(boxed) as *mut u8;
+        BoxedPhysicalKey {
+            // `Box::into_raw` never returns null.
+            ptr: unsafe { NonNull::new_unchecked(ptr) },
+            len,
+        }
+    }
+}
+
+impl From<BasicPhysicalKey> for BoxedPhysicalKey {
+    #[inline]
+    fn from(key: BasicPhysicalKey) -> BoxedPhysicalKey {
+        BoxedPhysicalKey::from(key.0)
+    }
+}
+
+impl Clone for BoxedPhysicalKey {
+    #[inline]
+    fn clone(&self) -> BoxedPhysicalKey {
+        BoxedPhysicalKey::from(self.as_std_slice().to_vec())
+    }
+}
+
+impl Drop for BoxedPhysicalKey {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            let _ = Box::from_raw(slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len));
+        }
+    }
+}
+
+impl Deref for BoxedPhysicalKey {
+    type Target = BasicPhysicalKeySlice;
+
+    #[inline]
+    fn deref(&self) -> &BasicPhysicalKeySlice {
+        self.as_physical_slice()
+    }
+}
+
+impl Debug for BoxedPhysicalKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self.as_physical_slice(), f)
+    }
+}
+
+impl Display for BoxedPhysicalKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+/// Inline capacity of [`InlinePhysicalKey`]. A key whose physical length is at most this
+/// many bytes avoids a heap allocation entirely. A key that fills `alloc_new`'s 40-byte
+/// logical capacity plus the 8-byte timestamp (48 bytes) does not fit and lives on the heap.
+const INLINE_CAP: usize = 39;
+
+/// A small-string-optimized owned physical key. The bytes are stored inline in a fixed
+/// buffer and spill to the heap only once `physical_len` exceeds `INLINE_CAP`; the enum
+/// variant distinguishes the two states, and the inline variant keeps its length in one
+/// byte. The building machinery
+/// (`BufferWriter` plus `append_ts` / `shrink_ts`) promotes from inline to heap
+/// transparently on overflow, and the `as_physical_std_slice` view is identical regardless
+/// of where the bytes live.
+pub enum InlinePhysicalKey {
+    Inline { buf: [u8; INLINE_CAP], len: u8 },
+    Heap(Vec<u8>),
+}
+
+impl InlinePhysicalKey {
+    #[inline]
+    pub fn new() -> InlinePhysicalKey {
+        InlinePhysicalKey::Inline {
+            buf: [0; INLINE_CAP],
+            len: 0,
+        }
+    }
+
+    #[inline]
+    pub fn as_physical_std_slice(&self) -> &[u8] {
+        match self {
+            InlinePhysicalKey::Inline { buf, len } => &buf[..*len as usize],
+            InlinePhysicalKey::Heap(vec) => vec.as_slice(),
+        }
+    }
+
+    #[inline]
+    pub fn as_physical_slice(&self) -> &BasicPhysicalKeySlice {
+        BasicPhysicalKeySlice::from_physical_std_slice(self.as_physical_std_slice())
+    }
+
+    #[inline]
+    pub fn physical_len(&self) -> usize {
+        match self {
+            InlinePhysicalKey::Inline { len, .. } => *len as usize,
+            InlinePhysicalKey::Heap(vec) => vec.len(),
+        }
+    }
+
+    #[inline]
+    pub fn get_ts(&self) -> u64 {
+        self.as_physical_slice().get_ts()
+    }
+
+    /// Promote an inline key to heap storage so that it can grow beyond `INLINE_CAP`, and
+    /// return a mutable reference to the backing `Vec`.
+    fn promote(&mut self) -> &mut Vec<u8> {
+        if let InlinePhysicalKey::Inline { buf, len } = self {
+            let vec = buf[..*len as usize].to_vec();
+            *self = InlinePhysicalKey::Heap(vec);
+        }
+        match self {
+            InlinePhysicalKey::Heap(vec) => vec,
+            InlinePhysicalKey::Inline { .. } => unreachable!(),
+        }
+    }
+
+    #[inline]
+    pub fn append_ts(&mut self, ts: u64) {
+        self.write_bytes(&MvccTsCodec::encode(ts)).unwrap();
+    }
+
+    #[inline]
+    pub fn shrink_ts(&mut self) {
+        let suffix_len = MvccTsCodec::SUFFIX_LEN;
+        match self {
+            InlinePhysicalKey::Inline { len, .. } => *len -= suffix_len as u8,
+            InlinePhysicalKey::Heap(vec) => {
+                let new_len = vec.len() - suffix_len;
+                vec.truncate(new_len);
+            }
+        }
+    }
+
+    #[inline]
+    pub fn into_vec(self) -> Vec<u8> {
+        match self {
+            InlinePhysicalKey::Inline { buf, len } => buf[..len as usize].to_vec(),
+            InlinePhysicalKey::Heap(vec) => vec,
+        }
+    }
+}
+
+impl Default for InlinePhysicalKey {
+    #[inline]
+    fn default() -> InlinePhysicalKey {
+        InlinePhysicalKey::new()
+    }
+}
+
+impl From<Vec<u8>> for InlinePhysicalKey {
+    #[inline]
+    fn from(vec: Vec<u8>) -> InlinePhysicalKey {
+        if vec.len() <= INLINE_CAP {
+            let mut buf = [0u8; INLINE_CAP];
+            buf[..vec.len()].copy_from_slice(&vec);
+            InlinePhysicalKey::Inline {
+                buf,
+                len: vec.len() as u8,
+            }
+        } else {
+            InlinePhysicalKey::Heap(vec)
+        }
+    }
+}
+
+impl BufferWriter for InlinePhysicalKey {
+    #[inline]
+    unsafe fn bytes_mut(&mut self, size: usize) -> &mut [u8] {
+        // Promote to the heap if the write would overflow the inline buffer.
+        if let InlinePhysicalKey::Inline { len, .. } = self {
+            if *len as usize + size > INLINE_CAP {
+                self.promote();
+            }
+        }
+        match self {
+            InlinePhysicalKey::Inline { buf, len } => &mut buf[*len as usize..],
+            InlinePhysicalKey::Heap(vec) => vec.bytes_mut(size),
+        }
+    }
+
+    #[inline]
+    unsafe fn advance_mut(&mut self, count: usize) {
+        match self {
+            InlinePhysicalKey::Inline { len, .. } => *len += count as u8,
+            InlinePhysicalKey::Heap(vec) => vec.advance_mut(count),
+        }
+    }
+
+    #[inline]
+    fn write_bytes(&mut self, values: &[u8]) -> codec::Result<()> {
+        if let InlinePhysicalKey::Inline { len, .. } = self {
+            if *len as usize + values.len() > INLINE_CAP {
+                self.promote();
+            }
+        }
+        match self {
+            InlinePhysicalKey::Inline { buf, len } => {
+                let start = *len as usize;
+                buf[start..start + values.len()].copy_from_slice(values);
+                *len += values.len() as u8;
+                Ok(())
+            }
+            InlinePhysicalKey::Heap(vec) => vec.write_bytes(values),
+        }
+    }
+}
+
+impl Deref for InlinePhysicalKey {
+    type Target = BasicPhysicalKeySlice;
+
+    #[inline]
+    fn deref(&self) -> &BasicPhysicalKeySlice {
+        self.as_physical_slice()
+    }
+}
+
 // Owned Logical Key is intentionally not provided to avoid abuse.
 
 #[derive(Hash, PartialEq, Eq, PartialOrd, Ord)]
@@ -613,8 +953,195 @@ impl ToPhysicalKeySlice<BasicPhysicalKeySlice> for Vec<u8> {
     }
 }
 
+/// A reference-counted shared physical key backed by an `Arc<[u8]>`.
+///
+/// Executor pipelines frequently pass the same key buffer (scan boundaries, group-by keys,
+/// join keys) between stages; the `Vec`-backed owned form must be deep-copied on each
+/// hand-off. A `SharedPhysicalKey` is `Clone`d by bumping an atomic refcount, so downstream
+/// stages share the bytes. It is zero-copy-convertible to `&BasicPhysicalKeySlice` for
+/// comparison and hashing, and participates in the [`PKContainer`] machinery by handing the
+/// `Arc` to the container's owner slot to keep the pointee alive past the borrow.
+#[derive(Clone)]
+pub struct SharedPhysicalKey {
+    data: Arc<[u8]>,
+}
+
+impl SharedPhysicalKey {
+    #[inline]
+    pub fn as_physical_std_slice(&self) -> &[u8] {
+        &self.data
+    }
+
+    #[inline]
+    pub fn as_physical_slice(&self) -> &BasicPhysicalKeySlice {
+        BasicPhysicalKeySlice::from_physical_std_slice(&self.data)
+    }
+
+    #[inline]
+    pub fn as_logical_slice(&self) -> &LogicalKeySlice {
+        self.as_physical_slice().as_logical_slice()
+    }
+
+    #[inline]
+    pub fn get_ts(&self) -> u64 {
+        self.as_physical_slice().get_ts()
+    }
+}
+
+impl From<Vec<u8>> for SharedPhysicalKey {
+    #[inline]
+    fn from(vec: Vec<u8>) -> SharedPhysicalKey {
+        SharedPhysicalKey {
+            data: Arc::from(vec.into_boxed_slice()),
+        }
+    }
+}
+
+impl Deref for SharedPhysicalKey {
+    type Target = BasicPhysicalKeySlice;
+
+    #[inline]
+    fn deref(&self) -> &BasicPhysicalKeySlice {
+        self.as_physical_slice()
+    }
+}
+
+impl Debug for SharedPhysicalKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self.as_physical_slice(), f)
+    }
+}
+
+impl Display for SharedPhysicalKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+impl ToPhysicalKeySlice<BasicPhysicalKeySlice> for SharedPhysicalKey {
+    // The owner holds a clone of the `Arc`, keeping the pointee alive for as long as the
+    // borrowed slice is reachable through the container.
+    type SliceOwner = Arc<[u8]>;
+
+    #[inline(never)]
+    fn to_physical_slice_container(&self) -> PKContainer<'_, Arc<[u8]>, BasicPhysicalKeySlice> {
+        let owner = Arc::clone(&self.data);
+        let r =
+            BasicPhysicalKeySlice::from_physical_std_slice(&owner[..]) as *const BasicPhysicalKeySlice;
+        unsafe { PKContainer::new(owner, r) }
+    }
+}
+
 // Assert PKContainer<'a, (), _> has zero space cost.
 assert_eq_size!(
     PKContainer<'static, (), BasicPhysicalKeySlice>,
     &'static BasicPhysicalKeySlice
 );
+
+/// Reports the owned heap bytes a key (or a container of keys) retains, so that TiKV's
+/// memory-usage subsystem can attribute key-buffer growth to the request holding it. This
+/// mirrors the `MallocSizeOf` pattern used by style/layout engines.
+///
+/// Owned keys report their *retained* footprint including any reserved-but-unused tail
+/// capacity, not just the logical length; borrowed slices own no heap and report zero.
+pub trait KeyMallocSizeOf {
+    /// The number of heap-allocated bytes owned by `self`, excluding `self`'s own inline
+    /// (stack / parent-buffer) size.
+    fn heap_size_of(&self) -> usize;
+}
+
+impl KeyMallocSizeOf for BasicPhysicalKey {
+    #[inline]
+    fn heap_size_of(&self) -> usize {
+        // The `Vec` keeps its capacity, not just its length, alive.
+        self.0.capacity()
+    }
+}
+
+impl KeyMallocSizeOf for BoxedPhysicalKey {
+    #[inline]
+    fn heap_size_of(&self) -> usize {
+        // A boxed slice retains exactly its length; there is no spare capacity word.
+        self.len
+    }
+}
+
+impl KeyMallocSizeOf for InlinePhysicalKey {
+    #[inline]
+    fn heap_size_of(&self) -> usize {
+        match self {
+            // Inline bytes live in the struct itself, so no heap is retained.
+            InlinePhysicalKey::Inline { .. } => 0,
+            InlinePhysicalKey::Heap(vec) => vec.capacity(),
+        }
+    }
+}
+
+impl KeyMallocSizeOf for BasicPhysicalKeySlice {
+    #[inline]
+    fn heap_size_of(&self) -> usize {
+        0
+    }
+}
+
+impl KeyMallocSizeOf for LogicalKeySlice {
+    #[inline]
+    fn heap_size_of(&self) -> usize {
+        0
+    }
+}
+
+impl<T: KeyMallocSizeOf> KeyMallocSizeOf for [T] {
+    #[inline]
+    fn heap_size_of(&self) -> usize {
+        // A borrowed slice does not own its backing array, only the heap reachable through
+        // each element.
+        self.iter().map(KeyMallocSizeOf::heap_size_of).sum()
+    }
+}
+
+impl<T: KeyMallocSizeOf> KeyMallocSizeOf for Vec<T> {
+    #[inline]
+    fn heap_size_of(&self) -> usize {
+        // The spine (`capacity` elements) plus whatever each element owns.
+        self.capacity() * std::mem::size_of::<T>() + self.as_slice().heap_size_of()
+    }
+}
+
+/// Lossless serde support for keys, for snapshot export and offline key-range tooling. Keys
+/// round-trip as their raw physical byte sequence (the hex `Debug`/`Display` impls stay the
+/// human-facing form); the `PHYSICAL_PREFIX` invariant is re-validated on deserialize.
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use super::*;
+    use serde::de::{Deserialize, Deserializer, Error as DeError};
+    use serde::ser::{Serialize, Serializer};
+
+    impl Serialize for BasicPhysicalKey {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(self.as_physical_std_slice())
+        }
+    }
+
+    impl<'de> Deserialize<'de> for BasicPhysicalKey {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let bytes = Vec::<u8>::deserialize(deserializer)?;
+            if !bytes.starts_with(Self::PHYSICAL_PREFIX) {
+                return Err(D::Error::custom("missing physical key prefix"));
+            }
+            Ok(Self::from_physical_vec(bytes))
+        }
+    }
+
+    impl Serialize for BasicPhysicalKeySlice {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(self.as_physical_std_slice())
+        }
+    }
+
+    impl Serialize for LogicalKeySlice {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(self.as_std_slice())
+        }
+    }
+}
@@ -14,6 +14,8 @@
 use std::mem;
 use std::sync::Arc;
 
+use tipb::executor::{ExecType, Executor as PbExecutor};
+use tipb::expression::{Expr, ExprType};
 use tipb::schema::ColumnInfo;
 use tipb::select::{Chunk, DAGRequest, SelectResponse};
 use kvproto::coprocessor::{KeyRange, Response};
@@ -21,6 +23,7 @@ use protobuf::{Message as PbMsg, RepeatedField};
 
 use coprocessor::codec::mysql;
 use coprocessor::codec::datum::{Datum, DatumEncoder};
+use coprocessor::codec::number;
 use coprocessor::select::xeval::EvalContext;
 use coprocessor::{Error, Result};
 use coprocessor::endpoint::{get_pk, prefix_next, to_pb_error, ReqContext};
@@ -59,7 +62,9 @@ impl DAGContext {
             req_ctx.fill_cache,
         );
 
-        let dag_executor = build_exec(req.take_executors().into_vec(), store, ranges, eval_ctx)?;
+        let executors = req.take_executors().into_vec();
+        let live_cols = LiveColumns::analyze(&executors, req.get_output_offsets());
+        let dag_executor = build_exec(executors, store, ranges, eval_ctx, live_cols)?;
         Ok(DAGContext {
             columns: dag_executor.columns,
             has_aggr: dag_executor.has_aggr,
@@ -164,6 +169,118 @@ impl DAGContext {
     }
 }
 
+/// The set of column offsets whose values are actually consumed somewhere in the
+/// executor pipeline. It is computed by a reverse-execution-order dataflow pass
+/// (see `LiveColumns::analyze`) and handed to the scan executor so that it only
+/// decodes the required column values from the raw row bytes.
+///
+/// The live set is stored as a bitset indexed by column offset.
+#[derive(Clone, Debug, Default)]
+pub struct LiveColumns {
+    words: Vec<u64>,
+}
+
+impl LiveColumns {
+    fn with_capacity(max_offset: usize) -> LiveColumns {
+        LiveColumns {
+            words: vec![0; max_offset / 64 + 1],
+        }
+    }
+
+    #[inline]
+    fn mark(&mut self, offset: usize) {
+        let word = offset / 64;
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        self.words[word] |= 1 << (offset % 64);
+    }
+
+    /// Returns whether the column at `offset` is live and therefore must be decoded.
+    #[inline]
+    pub fn is_live(&self, offset: usize) -> bool {
+        let word = offset / 64;
+        word < self.words.len() && (self.words[word] & (1 << (offset % 64))) != 0
+    }
+
+    /// Walks the executor list downward toward the table/index scan, starting from the
+    /// columns requested by `output_offsets` and unioning in every column offset
+    /// referenced by each executor's expressions (selection predicates, group-by keys,
+    /// aggregation arguments and TopN order expressions).
+    fn analyze(executors: &[PbExecutor], output_offsets: &[u32]) -> LiveColumns {
+        let max_offset = output_offsets
+            .iter()
+            .map(|&offset| offset as usize)
+            .max()
+            .unwrap_or(0);
+        let mut live = LiveColumns::with_capacity(max_offset);
+        for &offset in output_offsets {
+            live.mark(offset as usize);
+        }
+        // The executor list is ordered from the scan upward, so we walk it in reverse to
+        // propagate liveness from the top of the pipeline down to the scan.
+        for exec in executors.iter().rev() {
+            match exec.get_tp() {
+                ExecType::TypeSelection => for expr in exec.get_selection().get_conditions() {
+                    live.mark_expr(expr);
+                },
+                ExecType::TypeAggregation | ExecType::TypeStreamAgg => {
+                    let aggr = exec.get_aggregation();
+                    for expr in aggr.get_group_by() {
+                        live.mark_expr(expr);
+                    }
+                    for expr in aggr.get_agg_func() {
+                        live.mark_expr(expr);
+                    }
+                }
+                ExecType::TypeTopN => for item in exec.get_topN().get_order_by() {
+                    live.mark_expr(item.get_expr());
+                },
+                // Scans introduce columns rather than consuming them, but a few of their
+                // columns must never be pruned even when no expression references them:
+                // `inflate_cols` still resolves pk-handle columns (synthesized from
+                // `row.handle`) and columns carrying a default value or a NOT NULL flag, so
+                // those are seeded into the live set here.
+                ExecType::TypeTableScan => {
+                    Self::mark_special_columns(&mut live, exec.get_tbl_scan().get_columns());
+                }
+                ExecType::TypeIndexScan => {
+                    Self::mark_special_columns(&mut live, exec.get_idx_scan().get_columns());
+                }
+                // `Limit` carries no expressions, so it does not contribute to the live set.
+                _ => {}
+            }
+        }
+        live
+    }
+
+    /// Seeds the scan columns that `inflate_cols` can still resolve when they are absent
+    /// from the decoded row — pk-handle columns and columns with a default value or a NOT
+    /// NULL flag — so the pruning pass never drops a column the output path depends on.
+    fn mark_special_columns(live: &mut LiveColumns, columns: &[ColumnInfo]) {
+        for (offset, col) in columns.iter().enumerate() {
+            if col.get_pk_handle()
+                || col.has_default_val()
+                || mysql::has_not_null_flag(col.get_flag() as u64)
+            {
+                live.mark(offset);
+            }
+        }
+    }
+
+    /// Unions every column offset referenced by `expr` (and its children) into the set.
+    fn mark_expr(&mut self, expr: &Expr) {
+        if expr.get_tp() == ExprType::ColumnRef {
+            if let Ok(offset) = number::decode_i64(&mut expr.get_val()) {
+                self.mark(offset as usize);
+            }
+        }
+        for child in expr.get_children() {
+            self.mark_expr(child);
+        }
+    }
+}
+
 #[inline]
 fn inflate_cols(row: &Row, cols: &[ColumnInfo], output_offsets: &[u32]) -> Result<Vec<u8>> {
     let data = &row.data;
@@ -0,0 +1,79 @@
+// Copyright 2018 PingCAP, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::types::{RpnExpression, RpnExpressionNode};
+use crate::coprocessor::dag::expr::EvalContext;
+use crate::coprocessor::Result;
+
+/// Runs a build-time constant-folding pass over an RPN expression.
+///
+/// The RPN form is a post-order/stack representation, so we do a single left-to-right
+/// scan carrying a parallel stack of "is-constant" flags: `true` is pushed for literal
+/// constants and `false` for column references. When a pure function node consumes its
+/// `N` preceding entries and all of them are constant, the node is evaluated once against
+/// `ctx` and the whole subsequence is replaced by a single constant node (and `true` is
+/// pushed back onto the flag stack). This collapses expressions such as `NOT (1 AND 0)`
+/// to a single literal.
+///
+/// Folding is skipped for any function that is non-deterministic or that can raise a
+/// context-dependent runtime error, so overflow / out-of-range errors are still produced
+/// under exactly the same conditions as the unfolded form.
+pub fn fold_constants(ctx: &mut EvalContext, mut exp: RpnExpression) -> Result<RpnExpression> {
+    let src = exp.take_nodes();
+    // `out` accumulates the folded node sequence. `const_spans` is the parallel
+    // is-constant stack: each entry records whether the value currently on top of the
+    // evaluation stack is a compile-time constant and, if so, the index in `out` where
+    // the single node producing it begins.
+    let mut out: Vec<RpnExpressionNode> = Vec::with_capacity(src.len());
+    let mut const_spans: Vec<Option<usize>> = Vec::with_capacity(src.len());
+
+    for node in src {
+        match node {
+            RpnExpressionNode::Constant { .. } => {
+                const_spans.push(Some(out.len()));
+                out.push(node);
+            }
+            RpnExpressionNode::ColumnRef { .. } => {
+                const_spans.push(None);
+                out.push(node);
+            }
+            RpnExpressionNode::FnCall { ref func, .. } => {
+                let args_len = func.args_len();
+                let foldable = func.is_deterministic()
+                    && const_spans.len() >= args_len
+                    && const_spans[const_spans.len() - args_len..]
+                        .iter()
+                        .all(Option::is_some);
+                if foldable {
+                    // The operand nodes occupy a contiguous suffix of `out`; the earliest
+                    // of them marks where the folded constant should replace the subsequence.
+                    let begin = const_spans[const_spans.len() - args_len];
+                    let begin = begin.unwrap();
+                    out.push(node);
+                    let folded = super::types::eval_const_subsequence(ctx, &out[begin..])?;
+                    out.truncate(begin);
+                    out.push(folded);
+                    const_spans.truncate(const_spans.len() - args_len);
+                    const_spans.push(Some(begin));
+                } else {
+                    const_spans.truncate(const_spans.len() - args_len);
+                    const_spans.push(None);
+                    out.push(node);
+                }
+            }
+        }
+    }
+
+    exp.set_nodes(out);
+    Ok(exp)
+}
@@ -1,91 +1,191 @@
 // Copyright 2019 TiKV Project Authors. Licensed under Apache-2.0.
 
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+
 use super::types::RpnFnCallPayload;
+use crate::coprocessor::codec::mysql::{Decimal, Duration, Json, Time};
 use crate::coprocessor::dag::expr::EvalContext;
 use crate::coprocessor::Result;
 
-#[derive(Debug, Clone, Copy)]
-pub struct RpnFnEQReal;
+/// A comparison operator. Each operator maps a total `Ordering` between two operands to the
+/// boolean result of the comparison.
+pub trait CmpOp: Clone + Copy + std::fmt::Debug + Send + Sync + 'static {
+    fn ordering_to_bool(ordering: Ordering) -> bool;
+}
+
+macro_rules! impl_cmp_op {
+    ($name:ident, $($ordering:pat)|+) => {
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name;
+
+        impl CmpOp for $name {
+            #[inline]
+            fn ordering_to_bool(ordering: Ordering) -> bool {
+                match ordering {
+                    $($ordering)|+ => true,
+                    _ => false,
+                }
+            }
+        }
+    };
+}
 
-impl_template_fn! { 2 arg @ RpnFnEQReal }
+impl_cmp_op!(CmpOpEQ, Ordering::Equal);
+impl_cmp_op!(CmpOpNE, Ordering::Less | Ordering::Greater);
+impl_cmp_op!(CmpOpGT, Ordering::Greater);
+impl_cmp_op!(CmpOpGE, Ordering::Greater | Ordering::Equal);
+impl_cmp_op!(CmpOpLT, Ordering::Less);
+impl_cmp_op!(CmpOpLE, Ordering::Less | Ordering::Equal);
 
-impl RpnFnEQReal {
-    #[allow(clippy::float_cmp)]
+/// An operand type that participates in comparisons with a well-defined total order.
+pub trait Comparable: Clone + std::fmt::Debug + Send + Sync + 'static {
+    /// Total-order comparison of two present values.
+    fn compare(lhs: &Self, rhs: &Self) -> Ordering;
+}
+
+impl Comparable for f64 {
     #[inline]
-    fn call(
-        _ctx: &mut EvalContext,
-        _payload: RpnFnCallPayload<'_>,
-        arg0: &Option<f64>,
-        arg1: &Option<f64>,
-    ) -> Result<Option<i64>> {
-        // FIXME: It really should be a `Result<Option<f64>>`.
-        Ok(match (arg0, arg1) {
-            (Some(ref arg0), Some(ref arg1)) => Some((*arg0 == *arg1) as i64),
-            // TODO: Use `partial_cmp`.
-            _ => None,
+    fn compare(lhs: &f64, rhs: &f64) -> Ordering {
+        // `partial_cmp` yields `None` only for NaN; order NaN as the smallest value so the
+        // comparison stays total.
+        lhs.partial_cmp(rhs).unwrap_or_else(|| {
+            if lhs.is_nan() && rhs.is_nan() {
+                Ordering::Equal
+            } else if lhs.is_nan() {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            }
         })
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct RpnFnEQInt;
+macro_rules! impl_comparable_with_ord {
+    ($($ty:ty),+) => {
+        $(
+            impl Comparable for $ty {
+                #[inline]
+                fn compare(lhs: &$ty, rhs: &$ty) -> Ordering {
+                    Ord::cmp(lhs, rhs)
+                }
+            }
+        )+
+    };
+}
 
-impl_template_fn! { 2 arg @ RpnFnEQInt }
+impl_comparable_with_ord!(i64, Decimal, Vec<u8>, Time, Duration, Json);
 
-impl RpnFnEQInt {
+/// A generic comparison function over an operator `Op` and an operand type `T`. It returns
+/// `None` on any NULL input (SQL NULL propagation) and otherwise the operator applied to the
+/// total ordering of the two operands.
+#[derive(Debug, Clone, Copy)]
+pub struct RpnFnCompare<Op: CmpOp, T: Comparable> {
+    _phantom: PhantomData<(Op, T)>,
+}
+
+impl<Op: CmpOp, T: Comparable> RpnFnCompare<Op, T> {
     #[inline]
     fn call(
         _ctx: &mut EvalContext,
         _payload: RpnFnCallPayload<'_>,
-        arg0: &Option<i64>,
-        arg1: &Option<i64>,
+        arg0: &Option<T>,
+        arg1: &Option<T>,
     ) -> Result<Option<i64>> {
-        // FIXME: The algorithm here is incorrect. We should care about unsigned and signed.
         Ok(match (arg0, arg1) {
-            (Some(ref arg0), Some(ref arg1)) => Some((*arg0 == *arg1) as i64),
+            (Some(lhs), Some(rhs)) => Some(Op::ordering_to_bool(T::compare(lhs, rhs)) as i64),
             _ => None,
         })
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct RpnFnGTInt;
+/// Registers every (operator, type) pair as a concrete RPN function. Integer comparisons
+/// are intentionally *not* generated here: they need signedness-aware handling and are
+/// defined separately below.
+macro_rules! impl_compare_fns {
+    ($($op:ty),+ $(,)? ; $($ty:ty),+ $(,)?) => {
+        $($(
+            impl_template_fn! { 2 arg @ RpnFnCompare<$op, $ty> }
+        )+)+
+    };
+}
 
-impl_template_fn! { 2 arg @ RpnFnGTInt }
+impl_compare_fns! {
+    CmpOpEQ, CmpOpNE, CmpOpGT, CmpOpGE, CmpOpLT, CmpOpLE;
+    f64, Decimal, Vec<u8>, Time, Duration, Json,
+}
 
-impl RpnFnGTInt {
-    #[inline]
-    fn call(
-        _ctx: &mut EvalContext,
-        _payload: RpnFnCallPayload<'_>,
-        arg0: &Option<i64>,
-        arg1: &Option<i64>,
-    ) -> Result<Option<i64>> {
-        // FIXME: The algorithm here is incorrect. We should care about unsigned and signed.
-        Ok(match (arg0, arg1) {
-            (Some(ref arg0), Some(ref arg1)) => Some((*arg0 > *arg1) as i64),
-            _ => None,
-        })
+/// Three-valued integer comparison shared by the signedness-aware integer functions.
+/// Returns `None` when either operand is `None`, otherwise the MySQL-compatible ordering
+/// that respects the per-argument signedness taken from the call payload.
+#[inline]
+fn cmp_int(
+    lhs: &Option<i64>,
+    rhs: &Option<i64>,
+    lhs_unsigned: bool,
+    rhs_unsigned: bool,
+) -> Option<Ordering> {
+    match (lhs, rhs) {
+        (Some(lhs), Some(rhs)) => Some(match (lhs_unsigned, rhs_unsigned) {
+            (false, false) => lhs.cmp(rhs),
+            (true, true) => (*lhs as u64).cmp(&(*rhs as u64)),
+            (true, false) => {
+                if *rhs < 0 || *lhs < 0 {
+                    Ordering::Greater
+                } else {
+                    lhs.cmp(rhs)
+                }
+            }
+            (false, true) => {
+                if *lhs < 0 || *rhs < 0 {
+                    Ordering::Less
+                } else {
+                    lhs.cmp(rhs)
+                }
+            }
+        }),
+        _ => None,
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct RpnFnLTInt;
+#[inline]
+fn arg_is_unsigned(payload: &RpnFnCallPayload<'_>, index: usize) -> bool {
+    payload.field_type(index).is_unsigned()
+}
 
-impl_template_fn! { 2 arg @ RpnFnLTInt }
+/// A signedness-aware integer comparison function over an operator `Op`.
+#[derive(Debug, Clone, Copy)]
+pub struct RpnFnCompareInt<Op: CmpOp> {
+    _phantom: PhantomData<Op>,
+}
 
-impl RpnFnLTInt {
+impl<Op: CmpOp> RpnFnCompareInt<Op> {
     #[inline]
     fn call(
         _ctx: &mut EvalContext,
-        _payload: RpnFnCallPayload<'_>,
+        payload: RpnFnCallPayload<'_>,
         arg0: &Option<i64>,
         arg1: &Option<i64>,
     ) -> Result<Option<i64>> {
-        // FIXME: The algorithm here is incorrect. We should care about unsigned and signed.
-        Ok(match (arg0, arg1) {
-            (Some(ref arg0), Some(ref arg1)) => Some((*arg0 < *arg1) as i64),
-            _ => None,
-        })
+        let ord = cmp_int(
+            arg0,
+            arg1,
+            arg_is_unsigned(&payload, 0),
+            arg_is_unsigned(&payload, 1),
+        );
+        Ok(ord.map(|ord| Op::ordering_to_bool(ord) as i64))
     }
 }
+
+impl_template_fn! { 2 arg @ RpnFnCompareInt<CmpOpEQ> }
+impl_template_fn! { 2 arg @ RpnFnCompareInt<CmpOpNE> }
+impl_template_fn! { 2 arg @ RpnFnCompareInt<CmpOpGT> }
+impl_template_fn! { 2 arg @ RpnFnCompareInt<CmpOpGE> }
+impl_template_fn! { 2 arg @ RpnFnCompareInt<CmpOpLT> }
+impl_template_fn! { 2 arg @ RpnFnCompareInt<CmpOpLE> }
+
+// Backwards-compatible aliases for the originally hand-written structs.
+pub type RpnFnEQReal = RpnFnCompare<CmpOpEQ, f64>;
+pub type RpnFnEQInt = RpnFnCompareInt<CmpOpEQ>;
+pub type RpnFnGTInt = RpnFnCompareInt<CmpOpGT>;
+pub type RpnFnLTInt = RpnFnCompareInt<CmpOpLT>;
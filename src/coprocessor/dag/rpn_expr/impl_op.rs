@@ -15,6 +15,10 @@ use super::types::RpnFnCallPayload;
 use crate::coprocessor::dag::expr::EvalContext;
 use crate::coprocessor::Result;
 
+// All functions in this module are pure and deterministic (they neither read
+// mutable context state nor raise context-dependent runtime errors), so they are
+// eligible for the build-time constant-folding pass in `super::fold`.
+
 #[derive(Debug, Clone, Copy)]
 pub struct RpnFnLogicalAnd;
 
@@ -42,6 +46,42 @@ impl RpnFnLogicalAnd {
             },
         })
     }
+
+    /// Vectorized evaluation over a whole batch. Before the per-element pass a whole-batch
+    /// short-circuit checks whether `arg0` already resolves every row to known-false
+    /// (`Some(0)`); if so the output column is `Some(0)` throughout and `arg1` is never
+    /// inspected. Otherwise the per-element truth table is identical to `call`, preserving
+    /// three-valued NULL semantics.
+    #[inline]
+    fn call_batch(
+        _ctx: &mut EvalContext,
+        _payload: RpnFnCallPayload<'_>,
+        arg0: &[Option<i64>],
+        arg1: &[Option<i64>],
+    ) -> Result<Vec<Option<i64>>> {
+        // Whole-batch short-circuit: AND is known-false wherever the first operand is
+        // known-false regardless of the second, so a fully known-false `arg0` determines
+        // the entire column without touching `arg1`.
+        if arg0.iter().all(|v| *v == Some(0)) {
+            return Ok(vec![Some(0); arg0.len()]);
+        }
+        let mut out = Vec::with_capacity(arg0.len());
+        for i in 0..arg0.len() {
+            out.push(match arg0[i] {
+                None => match arg1[i] {
+                    Some(0) => Some(0),
+                    _ => None,
+                },
+                Some(0) => Some(0),
+                Some(_) => match arg1[i] {
+                    None => None,
+                    Some(0) => Some(0),
+                    Some(_) => Some(1),
+                },
+            });
+        }
+        Ok(out)
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -72,6 +112,46 @@ impl RpnFnLogicalOr {
             Some(_) => Some(1),
         })
     }
+
+    /// Vectorized evaluation over a whole batch. Before the per-element pass a whole-batch
+    /// short-circuit checks whether `arg0` already resolves every row to known-true (a
+    /// truthy `Some`); if so the output column is `Some(1)` throughout and `arg1` is never
+    /// inspected. Otherwise the per-element truth table is identical to `call`, preserving
+    /// three-valued NULL semantics.
+    #[inline]
+    fn call_batch(
+        _ctx: &mut EvalContext,
+        _payload: RpnFnCallPayload<'_>,
+        arg0: &[Option<i64>],
+        arg1: &[Option<i64>],
+    ) -> Result<Vec<Option<i64>>> {
+        // Whole-batch short-circuit: OR is known-true wherever the first operand is truthy
+        // regardless of the second, so a fully known-true `arg0` determines the entire
+        // column without touching `arg1`.
+        if arg0.iter().all(|v| match v {
+            Some(x) => *x != 0,
+            None => false,
+        }) {
+            return Ok(vec![Some(1); arg0.len()]);
+        }
+        let mut out = Vec::with_capacity(arg0.len());
+        for i in 0..arg0.len() {
+            out.push(match arg0[i] {
+                None => match arg1[i] {
+                    None => None,
+                    Some(0) => None,
+                    Some(_) => Some(1),
+                },
+                Some(0) => match arg1[i] {
+                    None => None,
+                    Some(0) => Some(0),
+                    Some(_) => Some(1),
+                },
+                Some(_) => Some(1),
+            });
+        }
+        Ok(out)
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -0,0 +1,220 @@
+// Copyright 2019 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! A memory-accounted spilling subsystem for coprocessor scan buffers.
+//!
+//! Query-engine executors such as sort and hash aggregation can accumulate unbounded
+//! amounts of intermediate data and OOM the node. [`Spiller`] bounds that memory: once the
+//! bytes buffered in memory cross a configurable budget, the oldest partitions are flushed
+//! to a temp file and streamed back on demand.
+//!
+//! Each flushed partition is prefixed with a header recording its length so it can be
+//! delimited on read-back. Temp files live under a dedicated spill directory that is wiped
+//! on startup (to drop residual dirs from crashed processes) and on `Drop`.
+//!
+//! Partitions are written with ordinary buffered file I/O: the spill file is transient
+//! scratch that never outlives the process, so there is nothing to fsync and no need for
+//! O_DIRECT, block alignment, or header padding. Callers drain one partition per call
+//! (`pop_front`), so only a single partition plus the in-memory budget is resident at a
+//! time regardless of how much has been spilled.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Size of the per-partition header: an 8-byte little-endian logical length.
+const PARTITION_HEADER_LEN: usize = 8;
+
+/// Configuration of the spilling subsystem. Spilling is off by default; it is enabled only
+/// when `enabled` is set and then bounded by `memory_budget` bytes.
+#[derive(Clone, Debug)]
+pub struct SpillConfig {
+    pub enabled: bool,
+    /// In-memory byte budget; crossing it triggers a flush of the oldest partitions.
+    pub memory_budget: usize,
+    /// Directory under which temp spill files are created.
+    pub dir: PathBuf,
+}
+
+impl Default for SpillConfig {
+    fn default() -> SpillConfig {
+        SpillConfig {
+            enabled: false,
+            memory_budget: 0,
+            dir: PathBuf::from("spill"),
+        }
+    }
+}
+
+/// Counters reported to `collect_statistics` for spill activity.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SpillStatistics {
+    pub local_spill_write_bytes: usize,
+    pub local_spill_read_bytes: usize,
+}
+
+impl SpillStatistics {
+    pub fn add(&mut self, other: &SpillStatistics) {
+        self.local_spill_write_bytes += other.local_spill_write_bytes;
+        self.local_spill_read_bytes += other.local_spill_read_bytes;
+    }
+}
+
+/// A single in-memory partition awaiting either consumption or a flush to disk.
+struct Partition {
+    data: Vec<u8>,
+}
+
+/// The on-disk location of a flushed partition.
+struct SpilledPartition {
+    offset: u64,
+    logical_len: usize,
+}
+
+pub struct Spiller {
+    config: SpillConfig,
+    buffered: Vec<Partition>,
+    buffered_bytes: usize,
+    spilled: Vec<SpilledPartition>,
+    file: Option<File>,
+    file_end: u64,
+    stats: SpillStatistics,
+}
+
+impl Spiller {
+    /// Create a spiller, wiping and recreating its spill directory first so that residual
+    /// files left by a crashed process are dropped.
+    pub fn new(config: SpillConfig) -> io::Result<Spiller> {
+        if config.enabled {
+            Self::wipe_dir(&config.dir)?;
+            fs::create_dir_all(&config.dir)?;
+        }
+        Ok(Spiller {
+            config,
+            buffered: Vec::new(),
+            buffered_bytes: 0,
+            spilled: Vec::new(),
+            file: None,
+            file_end: 0,
+            stats: SpillStatistics::default(),
+        })
+    }
+
+    fn wipe_dir(dir: &Path) -> io::Result<()> {
+        match fs::remove_dir_all(dir) {
+            Ok(()) => Ok(()),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Push a partition. If the in-memory budget is exceeded afterwards, the oldest
+    /// partitions are flushed to disk until the budget is satisfied again.
+    pub fn push(&mut self, data: Vec<u8>) -> io::Result<()> {
+        self.buffered_bytes += data.len();
+        self.buffered.push(Partition { data });
+        if self.config.enabled {
+            while self.buffered_bytes > self.config.memory_budget && !self.buffered.is_empty() {
+                self.flush_oldest()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn spill_file(&mut self) -> io::Result<&mut File> {
+        if self.file.is_none() {
+            let path = self.config.dir.join("partition.spill");
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(path)?;
+            self.file = Some(file);
+        }
+        Ok(self.file.as_mut().unwrap())
+    }
+
+    /// Flush the oldest buffered partition to disk, prefixed with its logical length.
+    fn flush_oldest(&mut self) -> io::Result<()> {
+        let partition = self.buffered.remove(0);
+        self.buffered_bytes -= partition.data.len();
+
+        let logical_len = partition.data.len();
+        let mut block = Vec::with_capacity(PARTITION_HEADER_LEN + logical_len);
+        block.extend_from_slice(&(logical_len as u64).to_le_bytes());
+        block.extend_from_slice(&partition.data);
+
+        let offset = self.file_end;
+        {
+            let file = self.spill_file()?;
+            file.seek(SeekFrom::Start(offset))?;
+            file.write_all(&block)?;
+        }
+        self.file_end += block.len() as u64;
+        self.stats.local_spill_write_bytes += block.len();
+        self.spilled.push(SpilledPartition {
+            offset,
+            logical_len,
+        });
+        Ok(())
+    }
+
+    /// Read one spilled partition back from disk, returning its logical bytes.
+    fn read_spilled(&mut self, partition: &SpilledPartition) -> io::Result<Vec<u8>> {
+        let block_len = PARTITION_HEADER_LEN + partition.logical_len;
+        let mut block = vec![0u8; block_len];
+        let file = self
+            .file
+            .as_mut()
+            .expect("spill file must exist while spilled partitions remain");
+        file.seek(SeekFrom::Start(partition.offset))?;
+        file.read_exact(&mut block)?;
+        self.stats.local_spill_read_bytes += block_len;
+        // Skip the header and return the logical bytes recorded at flush time.
+        Ok(block.split_off(PARTITION_HEADER_LEN))
+    }
+
+    /// Remove and return the oldest partition in first-in, first-out order, or `None` when
+    /// the spiller is empty. Partitions already flushed to disk are always older than the
+    /// ones still in memory (the oldest buffered partition is the first to be flushed), so
+    /// disk is drained ahead of memory to preserve the order of `push`.
+    ///
+    /// Callers pull one partition per call so the bytes handed back stay bounded to a single
+    /// batch: the rest of the scan keeps waiting — in memory up to the budget, on disk beyond
+    /// it — rather than being materialised all at once.
+    pub fn pop_front(&mut self) -> io::Result<Option<Vec<u8>>> {
+        if !self.spilled.is_empty() {
+            let partition = self.spilled.remove(0);
+            return self.read_spilled(&partition).map(Some);
+        }
+        if !self.buffered.is_empty() {
+            let partition = self.buffered.remove(0);
+            self.buffered_bytes -= partition.data.len();
+            return Ok(Some(partition.data));
+        }
+        Ok(None)
+    }
+
+    /// Whether any partition — on disk or in memory — is still awaiting consumption.
+    pub fn is_empty(&self) -> bool {
+        self.spilled.is_empty() && self.buffered.is_empty()
+    }
+
+    /// Take the accumulated spill counters, resetting them, mirroring the `take_statistics`
+    /// idiom the CF scanners use so the same bytes are never reported twice.
+    pub fn take_statistics(&mut self) -> SpillStatistics {
+        let stats = self.stats;
+        self.stats = SpillStatistics::default();
+        stats
+    }
+}
+
+impl Drop for Spiller {
+    fn drop(&mut self) {
+        // Drop the open handle before removing the directory.
+        self.file = None;
+        if self.config.enabled {
+            let _ = Self::wipe_dir(&self.config.dir);
+        }
+    }
+}
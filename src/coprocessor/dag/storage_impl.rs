@@ -1,29 +1,67 @@
 // Copyright 2019 TiKV Project Authors. Licensed under Apache-2.0.
 
+use std::mem;
+
 use tidb_query::storage::{IntervalRange, OwnedKvPair, PointRange, Result as QEResult, Storage};
 use tikv_util::buffer_vec::BufferVec;
 
+use super::spill::{SpillConfig, SpillStatistics, Spiller};
 use crate::coprocessor::Error;
 use crate::storage::Statistics;
 use crate::storage::{Key, RangeScanner, Scanner, Store};
 
+/// Target byte size of a spill partition. A freshly scanned batch is chopped into partitions
+/// on row boundaries: a partition is closed after the row that brings it to at least this
+/// size, so it exceeds the target by at most one row. The spiller can thus flush part of a
+/// wide batch to disk instead of holding (or spilling) the whole batch as one indivisible
+/// unit.
+const PARTITION_TARGET_BYTES: usize = 1024 * 1024;
+
+/// How many rows are pulled from the scanner at a time when spilling. A wide `n` is fetched
+/// in steps of this many rows, each step spilled before the next is scanned, so the rows
+/// resident in the scan buffers stay bounded regardless of the requested batch size.
+const SCAN_STEP_ROWS: usize = 1024;
+
 /// A `Storage` implementation over TiKV's storage.
 pub struct TiKVStorage<S: Store> {
     store: S,
     scanner: Option<S::Scanner>,
     range_scanner: Option<S::RangeScanner>,
     cf_stats_backlog: Statistics,
+    /// Optional memory-accounted spilling of scan buffers. `None` unless spilling is
+    /// enabled via `SpillConfig`.
+    spiller: Option<Spiller>,
+    /// Accumulated local-spill byte counters, folded from the spiller during
+    /// `collect_statistics` and surfaced via `take_spill_statistics`.
+    spill_stats: SpillStatistics,
 }
 
 impl<S: Store> TiKVStorage<S> {
     pub fn new(store: S) -> Self {
+        Self::with_spill_config(store, SpillConfig::default())
+    }
+
+    pub fn with_spill_config(store: S, spill_config: SpillConfig) -> Self {
+        let spiller = if spill_config.enabled {
+            Spiller::new(spill_config).ok()
+        } else {
+            None
+        };
         Self {
             store,
             scanner: None,
             range_scanner: None,
             cf_stats_backlog: Statistics::default(),
+            spiller,
+            spill_stats: SpillStatistics::default(),
         }
     }
+
+    /// The local-spill counters accumulated so far, for inclusion in request statistics.
+    /// They are folded from the spiller by `collect_statistics`.
+    pub fn take_spill_statistics(&self) -> SpillStatistics {
+        self.spill_stats
+    }
 }
 
 impl<S: Store> From<S> for TiKVStorage<S> {
@@ -82,11 +120,92 @@ impl<S: Store> Storage for TiKVStorage<S> {
         out_keys: &mut BufferVec,
         out_values: &mut BufferVec,
     ) -> QEResult<usize> {
-        Ok(self
-            .range_scanner
-            .as_mut()
-            .unwrap()
-            .next(n, out_keys, out_values)?)
+        let spiller = match self.spiller.as_mut() {
+            // No spilling: hand the scanner's output straight to the caller.
+            None => {
+                return self
+                    .range_scanner
+                    .as_mut()
+                    .unwrap()
+                    .next(n, out_keys, out_values);
+            }
+            Some(spiller) => spiller,
+        };
+
+        // Spilling path. The scan keeps a backlog of fixed-size partitions — recent ones in
+        // memory up to the budget, older ones flushed to disk — and returns exactly one
+        // partition per call. A backlog is drained before any fresh scanning, so at most one
+        // partition (plus the per-budget memory the spiller holds) is resident at a time and
+        // the rest of an oversized scan waits on disk instead of all landing in `out_*` at
+        // once. Spill byte counters accrue on the spiller and are folded into request
+        // statistics by `collect_statistics`.
+        if spiller.is_empty() {
+            // Backlog exhausted: scan the requested `n` rows, but pull them from the scanner
+            // in bounded steps and spill each step before fetching the next. This keeps the
+            // working set in `out_*` capped at one step's worth of rows rather than
+            // materialising the whole batch at once, so a wide `n` cannot blow the budget
+            // before the spiller ever sees the rows.
+            let base = out_keys.len();
+            let mut remaining = n;
+            let mut blob = Vec::new();
+            while remaining > 0 {
+                let step = remaining.min(SCAN_STEP_ROWS);
+                let start = out_keys.len();
+                self.range_scanner
+                    .as_mut()
+                    .unwrap()
+                    .next(step, out_keys, out_values)?;
+                let end = out_keys.len();
+                if end == start {
+                    // Scanner exhausted.
+                    break;
+                }
+                for i in start..end {
+                    let key = &out_keys[i];
+                    let value = &out_values[i];
+                    blob.extend_from_slice(&(key.len() as u32).to_le_bytes());
+                    blob.extend_from_slice(key);
+                    blob.extend_from_slice(&(value.len() as u32).to_le_bytes());
+                    blob.extend_from_slice(value);
+                    // Cap partition size so the spiller can shed part of a wide batch rather
+                    // than being forced to keep (or flush) the whole thing as one unit.
+                    if blob.len() >= PARTITION_TARGET_BYTES {
+                        spiller
+                            .push(mem::replace(&mut blob, Vec::new()))
+                            .map_err(Error::from)?;
+                    }
+                }
+                // Drop this step's rows from `out_*` before scanning the next one so the
+                // resident set never exceeds a single step.
+                out_keys.truncate(base);
+                out_values.truncate(base);
+                remaining -= end - start;
+            }
+            if !blob.is_empty() {
+                spiller.push(blob).map_err(Error::from)?;
+            }
+            if spiller.is_empty() {
+                // Scan exhausted and nothing was buffered.
+                return Ok(0);
+            }
+        }
+
+        // Return the oldest partition, decoding it back into the scan buffers.
+        let start = out_keys.len();
+        if let Some(partition) = spiller.pop_front().map_err(Error::from)? {
+            let mut off = 0;
+            while off + 4 <= partition.len() {
+                let klen = read_u32_le(&partition[off..off + 4]) as usize;
+                off += 4;
+                out_keys.push(&partition[off..off + klen]);
+                off += klen;
+                let vlen = read_u32_le(&partition[off..off + 4]) as usize;
+                off += 4;
+                out_values.push(&partition[off..off + vlen]);
+                off += vlen;
+            }
+        }
+        Ok(out_keys.len() - start)
     }
 
     fn get(&mut self, _is_key_only: bool, range: PointRange) -> QEResult<Option<OwnedKvPair>> {
@@ -107,5 +226,20 @@ impl<S: Store> Storage for TiKVStorage<S> {
         }
         dest.add(&self.cf_stats_backlog);
         self.cf_stats_backlog = Statistics::default();
+        // Fold the spiller's local-spill byte counters into the request statistics.
+        // `Statistics` has no spill field, so they are surfaced through
+        // `take_spill_statistics`; take-and-reset here keeps the same bytes from being
+        // reported twice across repeated `collect_statistics` calls.
+        if let Some(spiller) = self.spiller.as_mut() {
+            self.spill_stats.add(&spiller.take_statistics());
+        }
     }
 }
+
+/// Decode a little-endian `u32` from the front of `buf`.
+#[inline]
+fn read_u32_le(buf: &[u8]) -> u32 {
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(&buf[..4]);
+    u32::from_le_bytes(bytes)
+}
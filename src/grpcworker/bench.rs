@@ -0,0 +1,202 @@
+// Copyright 2018 PingCAP, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A built-in load generator for [`GrpcRequestWorker`].
+//!
+//! Modeled on a standalone stress tool (N concurrent client threads over a channel,
+//! configurable payload, aggregate throughput/latency), it fires `KvGetSubTask` requests
+//! round-robin across the configured [`Priority`] tiers (all four by default) and reports
+//! per-priority p50/p95/p99 latency, achieved QPS, and the observed `PoolBusy` rejection
+//! rate. Each client submits its requests back to back: the load is not paced to a target
+//! rate, and no coprocessor subtasks are generated. With the `TIKV_BENCH_FULL_PAYLOAD`
+//! switch set, requests use 4096-byte keys instead of 16-byte ones. The priority-isolation
+//! and back-pressure behaviour of `is_pool_busy` / `schedule_task` can be tuned empirically.
+
+use std::sync::mpsc::channel;
+use std::time::{Duration, Instant};
+
+use bench_util::use_full_payload;
+use kvproto::kvrpcpb;
+
+use super::{Callback, Error, GrpcRequestWorker, KvGetSubTask, Priority, Result, Value};
+
+const FULL_PAYLOAD_KEY_LEN: usize = 4096;
+
+/// Configuration of a single benchmark run.
+pub struct BenchConfig {
+    /// Number of concurrent client threads offering load.
+    pub clients: usize,
+    /// Number of requests each client fires.
+    pub requests_per_client: usize,
+    /// The priority mix to cycle through; each client round-robins over these tiers.
+    pub priorities: Vec<Priority>,
+}
+
+impl Default for BenchConfig {
+    fn default() -> BenchConfig {
+        BenchConfig {
+            clients: 8,
+            requests_per_client: 10_000,
+            priorities: vec![
+                Priority::ReadCritical,
+                Priority::ReadHigh,
+                Priority::ReadNormal,
+                Priority::ReadLow,
+            ],
+        }
+    }
+}
+
+/// Aggregated results for a single priority tier.
+#[derive(Default)]
+pub struct TierReport {
+    pub completed: usize,
+    pub rejected: usize,
+    latencies: Vec<Duration>,
+}
+
+impl TierReport {
+    fn percentile(&self, p: f64) -> Duration {
+        if self.latencies.is_empty() {
+            return Duration::from_secs(0);
+        }
+        let mut sorted = self.latencies.clone();
+        sorted.sort();
+        let rank = ((sorted.len() - 1) as f64 * p).round() as usize;
+        sorted[rank]
+    }
+
+    pub fn p50(&self) -> Duration {
+        self.percentile(0.50)
+    }
+
+    pub fn p95(&self) -> Duration {
+        self.percentile(0.95)
+    }
+
+    pub fn p99(&self) -> Duration {
+        self.percentile(0.99)
+    }
+
+    /// Fraction of offered requests that were rejected with `PoolBusy`.
+    pub fn rejection_rate(&self) -> f64 {
+        let total = self.completed + self.rejected;
+        if total == 0 {
+            0.0
+        } else {
+            self.rejected as f64 / total as f64
+        }
+    }
+}
+
+fn tier_index(priority: Priority) -> usize {
+    match priority {
+        Priority::ReadCritical => 0,
+        Priority::ReadHigh => 1,
+        Priority::ReadNormal => 2,
+        Priority::ReadLow => 3,
+    }
+}
+
+/// A single benchmark sample: the tier it targeted, the end-to-end latency, and whether
+/// the request was rejected by back-pressure.
+struct Sample {
+    tier: usize,
+    latency: Duration,
+    rejected: bool,
+}
+
+fn make_callback(tier: usize, sent_at: Instant, done: ::std::sync::mpsc::Sender<Sample>) -> Callback {
+    Box::new(move |result: Result| {
+        let rejected = match result {
+            Err(Error::PoolBusy(_)) => true,
+            _ => false,
+        };
+        let _ = done.send(Sample {
+            tier,
+            latency: sent_at.elapsed(),
+            rejected,
+        });
+    })
+}
+
+/// Drive `worker` with the offered load described by `config` and return a per-tier report
+/// plus the achieved aggregate QPS.
+pub fn run(worker: &GrpcRequestWorker, config: &BenchConfig) -> ([TierReport; 4], f64) {
+    let key_len = if use_full_payload() {
+        FULL_PAYLOAD_KEY_LEN
+    } else {
+        16
+    };
+    let total = config.clients * config.requests_per_client;
+    let (tx, rx) = channel::<Sample>();
+
+    let start = Instant::now();
+    let mut client_handles = Vec::with_capacity(config.clients);
+    for client_id in 0..config.clients {
+        let worker = worker.clone();
+        let tx = tx.clone();
+        let priorities = config.priorities.clone();
+        let requests = config.requests_per_client;
+        client_handles.push(::std::thread::spawn(move || {
+            for i in 0..requests {
+                let priority = priorities[(client_id + i) % priorities.len()];
+                let tier = tier_index(priority);
+                let subtask = KvGetSubTask {
+                    req_context: kvrpcpb::Context::new(),
+                    key: vec![b'k'; key_len],
+                    start_ts: 1,
+                };
+                worker.async_execute(
+                    box subtask,
+                    priority,
+                    make_callback(tier, Instant::now(), tx.clone()),
+                );
+            }
+        }));
+    }
+    drop(tx);
+    for handle in client_handles {
+        let _ = handle.join();
+    }
+
+    let mut reports: [TierReport; 4] = [
+        TierReport::default(),
+        TierReport::default(),
+        TierReport::default(),
+        TierReport::default(),
+    ];
+    let mut collected = 0;
+    for sample in rx.iter() {
+        let report = &mut reports[sample.tier];
+        if sample.rejected {
+            report.rejected += 1;
+        } else {
+            report.completed += 1;
+            report.latencies.push(sample.latency);
+        }
+        collected += 1;
+        if collected == total {
+            break;
+        }
+    }
+
+    let elapsed = start.elapsed();
+    let qps = if elapsed.as_secs() == 0 && elapsed.subsec_nanos() == 0 {
+        0.0
+    } else {
+        let secs = elapsed.as_secs() as f64 + f64::from(elapsed.subsec_nanos()) / 1e9;
+        total as f64 / secs
+    };
+    (reports, qps)
+}
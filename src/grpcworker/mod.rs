@@ -13,11 +13,16 @@
 
 mod task;
 mod errors;
+mod morsel;
+pub mod bench;
 
-use std::{io, result, sync};
+use std::{io, mem, result, sync, thread};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+use std::time::{Duration, Instant};
 
 use util::threadpool::{self, ThreadPool, ThreadPoolBuilder};
-use util::worker::{Runnable, ScheduleError, Scheduler, Worker};
+use util::worker::{Runnable, RunnableWithTimer, ScheduleError, Scheduler, Timer, Worker};
 use storage::Engine;
 use server::Config;
 use kvproto::kvrpcpb;
@@ -56,6 +61,7 @@ impl threadpool::ContextFactory<WorkerThreadContext> for WorkerThreadContextFact
             end_point_batch_row_limit: self.end_point_batch_row_limit,
             end_point_recursion_limit: self.end_point_recursion_limit,
             engine: self.engine.clone(),
+            broadcast_epoch: 0,
         }
     }
 }
@@ -64,6 +70,10 @@ pub struct WorkerThreadContext {
     end_point_batch_row_limit: usize,
     end_point_recursion_limit: u32,
     engine: Box<Engine>,
+    /// The generation of the last broadcast this thread ran, used by `broadcast_on_pool`
+    /// to ensure each thread applies a given broadcast closure exactly once. Zero means no
+    /// broadcast has run on this thread yet; generations handed out start at one.
+    broadcast_epoch: usize,
 }
 
 impl threadpool::Context for WorkerThreadContext {}
@@ -83,6 +93,93 @@ fn schedule_task(scheduler: &Scheduler<Task>, t: Task) {
     }
 }
 
+/// Drive a single task one subtask forward on the given worker context, re-scheduling it
+/// through `scheduler` when the subtask continues or yields and invoking its callback when
+/// it finishes.
+#[inline]
+fn drive_task(context: &mut WorkerThreadContext, scheduler: Scheduler<Task>, mut t: Task) {
+    let subtask = t.subtask.take().unwrap();
+    subtask.async_work(
+        context,
+        box move |result: task::SubTaskResult| match result {
+            task::SubTaskResult::Continue(new_subtask) => {
+                t.subtask = Some(new_subtask);
+                schedule_task(&scheduler, t);
+            }
+            // A subtask that exhausted its per-execution budget returns `Yield` with its
+            // saved resume state. It is re-scheduled onto the back of its tier (same as
+            // `Continue`) so sibling tasks queued behind it get a turn before it resumes
+            // exactly where it stopped.
+            task::SubTaskResult::Yield(resume_subtask) => {
+                t.subtask = Some(resume_subtask);
+                schedule_task(&scheduler, t);
+            }
+            task::SubTaskResult::Finish(result) => {
+                (t.callback)(result);
+            }
+        },
+    );
+}
+
+/// Selects which pool(s) a broadcast should run on.
+#[derive(Clone, Copy)]
+pub enum BroadcastTarget {
+    Pool(Priority),
+    AllPools,
+}
+
+/// A closure broadcast to every worker thread. It is a shared trait object so the same
+/// closure can be cloned cheaply across every thread of every targeted pool.
+pub type BroadcastFn = Arc<dyn Fn(&mut WorkerThreadContext) + Send + Sync>;
+
+/// Hands out a fresh, process-wide broadcast generation on every call. Tagging each
+/// broadcast with its own generation lets a worker thread tell whether it has already run
+/// the current broadcast (see `WorkerThreadContext::broadcast_epoch`).
+static BROADCAST_EPOCH: AtomicUsize = ATOMIC_USIZE_INIT;
+
+/// Run `f` exactly once on every worker thread of `pool`.
+///
+/// Unlike a barrier-based broadcast, this does not assume every worker is idle when the
+/// broadcast arrives. We tag the broadcast with a unique generation and flood the pool with
+/// cheap "probe" closures: the first probe to reach a thread whose `broadcast_epoch` is
+/// behind the current generation runs `f` there and advances that thread's epoch; every
+/// later probe on the same thread is a no-op. Because probes never block, a worker still
+/// busy with an earlier subtask simply runs a probe once it frees, rather than stalling the
+/// broadcast — so no thread can pick up two closures and deadlock a fixed-size barrier.
+/// Probes are topped up until every thread has been visited, which is the join point.
+fn broadcast_on_pool(pool: &ThreadPool<WorkerThreadContext>, concurrency: usize, f: BroadcastFn) {
+    if concurrency == 0 {
+        return;
+    }
+    let epoch = BROADCAST_EPOCH.fetch_add(1, Ordering::SeqCst) + 1;
+    let visited = Arc::new(AtomicUsize::new(0));
+    // Keep feeding probes until every thread has run `f` exactly once. Each round submits
+    // one probe per thread; probes landing on an already-visited thread return immediately,
+    // so a thread busy with an earlier subtask is revisited on a later round once free.
+    while visited.load(Ordering::Acquire) < concurrency {
+        for _ in 0..concurrency {
+            let f = Arc::clone(&f);
+            let visited = Arc::clone(&visited);
+            pool.execute(move |context: &mut WorkerThreadContext| {
+                if context.broadcast_epoch != epoch {
+                    context.broadcast_epoch = epoch;
+                    f(context);
+                    visited.fetch_add(1, Ordering::Release);
+                }
+            });
+        }
+        // Let the queued probes drain before checking coverage again. Probes are cheap and
+        // non-blocking, so a short sleep keeps this from spinning hot while still topping up
+        // for any worker that was busy when the previous round ran.
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+/// After this many consecutive higher-tier dispatches a worker is forced to drain at
+/// least one task from the lowest non-empty tier, so that low-priority tasks cannot be
+/// starved indefinitely by a steady stream of higher-priority work.
+const ANTI_STARVATION_EPOCH: u32 = 64;
+
 struct Runner {
     pool_read_critical: ThreadPool<WorkerThreadContext>,
     pool_read_high: ThreadPool<WorkerThreadContext>,
@@ -90,6 +187,27 @@ struct Runner {
     pool_read_low: ThreadPool<WorkerThreadContext>,
     max_read_tasks: usize,
 
+    /// Counts consecutive higher-tier dispatches for the anti-starvation guarantee.
+    high_tier_epoch: u32,
+
+    /// When non-zero, incoming tasks are accumulated into per-tier batches for up to this
+    /// interval (or until a batch reaches `max_read_tasks`) and then handed to a worker in
+    /// a single `execute` call, cutting the per-task wakeup/continuation overhead under
+    /// high QPS. A zero interval disables batching and preserves lightly-loaded latency.
+    throttle_interval: Duration,
+
+    /// Per-tier accumulation buffers, ordered high-to-low (critical, high, normal, low).
+    batches: [Vec<Task>; 4],
+
+    /// The instant the oldest task in each tier's batch was enqueued, so a partially-filled
+    /// batch can be flushed once it has been open for `throttle_interval`, bounding tail
+    /// latency to the configured quantum rather than the worker's coarser tick cadence.
+    /// `None` when the batch is empty.
+    batch_since: [Option<Instant>; 4],
+
+    /// Thread count of each pool, ordered high-to-low, used to size broadcasts.
+    concurrency: [usize; 4],
+
     scheduler: Scheduler<Task>,
 }
 
@@ -105,41 +223,203 @@ impl Runner {
         }
     }
 
+    /// The pools ordered from the highest tier to the lowest.
+    #[inline]
+    fn pools_high_to_low(&self) -> [&ThreadPool<WorkerThreadContext>; 4] {
+        [
+            &self.pool_read_critical,
+            &self.pool_read_high,
+            &self.pool_read_normal,
+            &self.pool_read_low,
+        ]
+    }
+
     /// Check whether tasks in the pool exceeds the limit.
     #[inline]
     fn is_pool_busy(&self, pool: &ThreadPool<WorkerThreadContext>) -> bool {
         pool.get_task_count() >= self.max_read_tasks
     }
+
+    /// The batch buffer index for a priority tier, ordered high-to-low.
+    #[inline]
+    fn tier_index(priority: Priority) -> usize {
+        match priority {
+            Priority::ReadCritical => 0,
+            Priority::ReadHigh => 1,
+            Priority::ReadNormal => 2,
+            Priority::ReadLow => 3,
+        }
+    }
+
+    /// The priority tier for a batch buffer index, the inverse of `tier_index`.
+    #[inline]
+    fn priority_for_tier(index: usize) -> Priority {
+        match index {
+            0 => Priority::ReadCritical,
+            1 => Priority::ReadHigh,
+            2 => Priority::ReadNormal,
+            _ => Priority::ReadLow,
+        }
+    }
+
+    /// Hand a whole batch of same-tier tasks to a single worker that drains them in a loop
+    /// before returning, so the pool pays one `execute` hop for the entire batch.
+    fn flush_batch(&mut self, priority: Priority) {
+        let index = Self::tier_index(priority);
+        let batch = mem::replace(&mut self.batches[index], Vec::new());
+        self.batch_since[index] = None;
+        if batch.is_empty() {
+            return;
+        }
+        let scheduler = self.scheduler.clone();
+        let pool = match self.select_pool(priority) {
+            Some(pool) => pool,
+            None => {
+                for t in batch {
+                    let task_detail = format!("{}", t);
+                    (t.callback)(Err(Error::PoolBusy(task_detail)));
+                }
+                return;
+            }
+        };
+        pool.execute(move |context: &mut WorkerThreadContext| {
+            for t in batch {
+                drive_task(context, scheduler.clone(), t);
+            }
+        });
+    }
+
+    /// Run `f` once on every worker thread context of the targeted pool(s), blocking until
+    /// all threads have finished. Each pool is broadcast to independently so that a single
+    /// `f` can, for example, push a new `Engine` snapshot or reset `ExecutorMetrics` across
+    /// every thread without racing normal task scheduling.
+    fn broadcast(&self, target: BroadcastTarget, f: BroadcastFn) {
+        let pools = self.pools_high_to_low();
+        match target {
+            BroadcastTarget::Pool(priority) => {
+                let index = Self::tier_index(priority);
+                broadcast_on_pool(pools[index], self.concurrency[index], Arc::clone(&f));
+            }
+            BroadcastTarget::AllPools => {
+                for (index, pool) in pools.iter().enumerate() {
+                    broadcast_on_pool(pool, self.concurrency[index], Arc::clone(&f));
+                }
+            }
+        }
+    }
+
+    /// Force the lowest non-empty tier to make progress when the anti-starvation epoch
+    /// trips. In throttling mode the buffered lower-tier work lives in the per-tier
+    /// batches, so the lowest non-empty one is flushed straight to a worker (stealing onto
+    /// an idle higher pool via `select_pool` if its own tier is saturated); the lowest tier
+    /// is tried first so it is the one guaranteed a dispatch. When no batching is active,
+    /// lower-tier tasks are never held at the scheduler — they are dispatched on arrival —
+    /// so there is nothing to force and this is a no-op.
+    fn force_drain_lowest_tier(&mut self) {
+        for &priority in &[Priority::ReadLow, Priority::ReadNormal, Priority::ReadHigh] {
+            if !self.batches[Self::tier_index(priority)].is_empty() {
+                self.flush_batch(priority);
+                return;
+            }
+        }
+    }
+
+    /// Flush every partially-filled batch during shutdown, so no callback is ever dropped.
+    fn flush_all_batches(&mut self) {
+        self.flush_batch(Priority::ReadCritical);
+        self.flush_batch(Priority::ReadHigh);
+        self.flush_batch(Priority::ReadNormal);
+        self.flush_batch(Priority::ReadLow);
+    }
+
+    /// Select the pool a task should run on. A task prefers its own tier, but when that
+    /// tier is saturated an idle strictly-higher tier may "steal" the work (its threads
+    /// would otherwise sit useless), which keeps low-priority latency bounded while the
+    /// higher tiers are idle. Returns `None` when every eligible pool is busy.
+    fn select_pool(&self, priority: Priority) -> Option<&ThreadPool<WorkerThreadContext>> {
+        let preferred = self.get_pool_by_priority(priority);
+        if !self.is_pool_busy(preferred) {
+            return Some(preferred);
+        }
+        // Steal upward: an idle higher tier can run this lower-tier task.
+        for pool in self.pools_high_to_low().iter() {
+            if !self.is_pool_busy(pool) {
+                return Some(pool);
+            }
+            // Do not look past the preferred tier: a lower tier must never run
+            // higher-priority work.
+            if ::std::ptr::eq(*pool, preferred) {
+                break;
+            }
+        }
+        None
+    }
 }
 
 impl Runnable<Task> for Runner {
     fn run(&mut self, mut t: Task) {
         let scheduler = self.scheduler.clone();
-        let pool = self.get_pool_by_priority(t.priority);
-        if self.is_pool_busy(pool) {
-            let task_detail = format!("{}", t);
-            (t.callback)(Err(Error::PoolBusy(task_detail)));
+
+        // A broadcast control task carries a closure to run on every worker thread instead
+        // of a subtask chain. Dispatch it and return; it never enters the pool-selection or
+        // batching paths below.
+        if let Some((target, f, done)) = t.take_broadcast() {
+            self.broadcast(target, f);
+            done();
             return;
         }
 
+        // Anti-starvation: after a run of higher-tier dispatches, force the lowest
+        // non-empty tier to make progress *before* the incoming higher-tier task is
+        // handled, so that a steady higher-tier stream cannot hold the low tier back
+        // indefinitely.
+        if t.priority != Priority::ReadLow {
+            self.high_tier_epoch += 1;
+        } else {
+            self.high_tier_epoch = 0;
+        }
+        if self.high_tier_epoch >= ANTI_STARVATION_EPOCH {
+            self.high_tier_epoch = 0;
+            self.force_drain_lowest_tier();
+        }
+
+        // Throttling mode: accumulate into the tier batch, flushing once it is full or
+        // once the batch has been open for the configured quantum. Batches that do not fill
+        // or age out within a quantum are drained by the throttle timer (`on_timeout`) or at
+        // shutdown.
+        if self.throttle_interval > Duration::from_secs(0) {
+            let priority = t.priority;
+            let index = Self::tier_index(priority);
+            let now = Instant::now();
+            if self.batches[index].is_empty() {
+                self.batch_since[index] = Some(now);
+            }
+            self.batches[index].push(t);
+            let aged_out = self.batch_since[index]
+                .map_or(false, |since| now.duration_since(since) >= self.throttle_interval);
+            if self.batches[index].len() >= self.max_read_tasks || aged_out {
+                self.flush_batch(priority);
+            }
+            return;
+        }
+
+        let pool = match self.select_pool(t.priority) {
+            Some(pool) => pool,
+            None => {
+                let task_detail = format!("{}", t);
+                (t.callback)(Err(Error::PoolBusy(task_detail)));
+                return;
+            }
+        };
+
         pool.execute(move |context: &mut WorkerThreadContext| {
-            let subtask = t.subtask.take().unwrap();
-            subtask.async_work(
-                context,
-                box move |result: task::SubTaskResult| match result {
-                    task::SubTaskResult::Continue(new_subtask) => {
-                        t.subtask = Some(new_subtask);
-                        schedule_task(&scheduler, t);
-                    }
-                    task::SubTaskResult::Finish(result) => {
-                        (t.callback)(result);
-                    }
-                },
-            );
+            drive_task(context, scheduler, t);
         });
     }
 
     fn shutdown(&mut self) {
+        // Flush any partially-filled batch so no callback is dropped.
+        self.flush_all_batches();
         // Thread pools are built somewhere else while their ownerships are passed to the runner.
         // So the runner is responsible for destroying the thread pools.
         if let Err(e) = self.pool_read_critical.stop() {
@@ -157,6 +437,29 @@ impl Runnable<Task> for Runner {
     }
 }
 
+/// The token granularity of the throttling flush timer. A single recurring task is
+/// re-armed at `throttle_interval` after every fire.
+const THROTTLE_TIMER_TOKEN: () = ();
+
+impl RunnableWithTimer<Task, ()> for Runner {
+    /// Flush any batch that has been open at least one quantum. The scheduler's own tick
+    /// only fires when tasks arrive, so a lone throttled request would otherwise wait for
+    /// the next same-tier arrival (or shutdown) to be dispatched. This timer fires on a
+    /// `throttle_interval` cadence regardless of traffic, bounding a batch's tail latency to
+    /// the configured quantum, and re-arms itself for the next window.
+    fn on_timeout(&mut self, timer: &mut Timer<()>, _token: ()) {
+        let now = Instant::now();
+        for index in 0..self.batches.len() {
+            let aged_out = self.batch_since[index]
+                .map_or(false, |since| now.duration_since(since) >= self.throttle_interval);
+            if aged_out {
+                self.flush_batch(Self::priority_for_tier(index));
+            }
+        }
+        timer.add_task(self.throttle_interval, THROTTLE_TIMER_TOKEN);
+    }
+}
+
 pub struct GrpcRequestWorker {
     read_critical_concurrency: usize,
     read_high_concurrency: usize,
@@ -164,6 +467,7 @@ pub struct GrpcRequestWorker {
     read_low_concurrency: usize,
     max_read_tasks: usize,
     stack_size: usize,
+    throttle_interval: Duration,
 
     end_point_batch_row_limit: usize,
     end_point_recursion_limit: u32,
@@ -190,6 +494,7 @@ impl GrpcRequestWorker {
             read_low_concurrency: config.grpc_worker_read_low_concurrency,
             max_read_tasks: config.grpc_worker_max_read_tasks,
             stack_size: config.grpc_worker_stack_size.0 as usize,
+            throttle_interval: config.grpc_worker_throttle_interval.0,
 
             // Available in runner thread contexts
             end_point_batch_row_limit: config.end_point_batch_row_limit,
@@ -202,6 +507,18 @@ impl GrpcRequestWorker {
         }
     }
 
+    /// The worker-thread count of the pool that serves `priority`, used to cap how many
+    /// morsels a single request is fanned out into so the parallelism never exceeds the
+    /// threads available to run it.
+    pub(crate) fn concurrency_for(&self, priority: Priority) -> usize {
+        match priority {
+            Priority::ReadCritical => self.read_critical_concurrency,
+            Priority::ReadHigh => self.read_high_concurrency,
+            Priority::ReadNormal => self.read_normal_concurrency,
+            Priority::ReadLow => self.read_low_concurrency,
+        }
+    }
+
     /// Execute a task on the specified thread pool and get the result when it is finished.
     ///
     /// The caller should ensure the matching of the sub task and its priority, for example, for
@@ -221,6 +538,28 @@ impl GrpcRequestWorker {
         schedule_task(&self.scheduler, t);
     }
 
+    /// Run `f` exactly once on every worker thread context of the targeted pool(s),
+    /// blocking until all of them have completed. This is delivered as a broadcast control
+    /// task through the scheduler so it is serialized against normal task dispatch rather
+    /// than racing it.
+    pub fn broadcast<F>(&self, target: BroadcastTarget, f: F)
+    where
+        F: Fn(&mut WorkerThreadContext) + Send + Sync + 'static,
+    {
+        let f: BroadcastFn = Arc::new(f);
+        let (done_tx, done_rx) = sync::mpsc::channel();
+        let t = Task::new_broadcast(
+            target,
+            f,
+            Box::new(move || {
+                let _ = done_tx.send(());
+            }),
+        );
+        schedule_task(&self.scheduler, t);
+        // Block until the runner reports the broadcast has finished on every thread.
+        let _ = done_rx.recv();
+    }
+
     pub fn start(&mut self) -> result::Result<(), io::Error> {
         let thread_context_factory = WorkerThreadContextFactory {
             end_point_recursion_limit: self.end_point_recursion_limit,
@@ -230,6 +569,16 @@ impl GrpcRequestWorker {
         let mut worker = self.worker.lock().unwrap();
         let runner = Runner {
             max_read_tasks: self.max_read_tasks,
+            high_tier_epoch: 0,
+            throttle_interval: self.throttle_interval,
+            batches: [Vec::new(), Vec::new(), Vec::new(), Vec::new()],
+            batch_since: [None; 4],
+            concurrency: [
+                self.read_critical_concurrency,
+                self.read_high_concurrency,
+                self.read_normal_concurrency,
+                self.read_low_concurrency,
+            ],
             pool_read_critical: ThreadPoolBuilder::new(
                 thd_name!("grpcwkr-rc"),
                 thread_context_factory.clone(),
@@ -256,7 +605,17 @@ impl GrpcRequestWorker {
                 .build(),
             scheduler: self.scheduler.clone(),
         };
-        worker.start(runner)
+        // In throttling mode, drive a recurring timer at the quantum so a batch that never
+        // fills and sees no further arrivals is still flushed within `throttle_interval`
+        // rather than waiting on the traffic-driven scheduler tick. Without throttling there
+        // is nothing to flush, so the plain run loop is used.
+        if self.throttle_interval > Duration::from_secs(0) {
+            let mut timer = Timer::new(1);
+            timer.add_task(self.throttle_interval, THROTTLE_TIMER_TOKEN);
+            worker.start_with_timer(runner, timer)
+        } else {
+            worker.start(runner)
+        }
     }
 
     pub fn shutdown(&mut self) {
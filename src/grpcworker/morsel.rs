@@ -0,0 +1,241 @@
+// Copyright 2018 PingCAP, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Morsel-driven parallelism for a single coprocessor DAG request.
+//!
+//! A single coprocessor request over a wide key range would otherwise run as one
+//! `SubTask` chain bound to one worker thread. To let the idle cores of a pool help out,
+//! each wide interval range is split into "morsels": contiguous key sub-ranges of roughly
+//! equal key-space width, at most as many per range as the owning pool has threads. Morsels
+//! have no fixed row count or byte size. They are dispatched as independent subtasks
+//! through [`GrpcRequestWorker::async_execute`]. Each morsel produces a partial
+//! [`coppb::Response`] whose [`ExecutorMetrics`] are combined via `collect_metrics_into` as
+//! it arrives. There is no separate merge subtask: the callback of the last morsel to
+//! report concatenates the partial results in key order and invokes the request's callback.
+
+use std::sync::{Arc, Mutex};
+
+use kvproto::coprocessor as coppb;
+
+use coprocessor::util::is_point;
+
+use super::task::cop::{CopSubTask, ExecutorMetrics};
+use super::{Callback, Error, GrpcRequestWorker, Priority, Result, Value};
+
+/// A single unit of morsel-driven work: a contiguous key sub-range tagged with the index
+/// it occupies in the original range list, plus a global emission order. A wide range is
+/// chopped into several morsels that share a `range_index` but carry distinct, ascending
+/// `order` values, so a downstream merge can restore the scan's key order deterministically.
+pub struct Morsel {
+    pub range_index: usize,
+    /// Position of this morsel in ascending key order across the whole request, used to
+    /// concatenate the partial responses back in order.
+    pub order: usize,
+    pub range: coppb::KeyRange,
+}
+
+/// Read up to the leading eight bytes of `key` as a big-endian `u64`, zero-padded on the
+/// right, to interpolate split points across a key range.
+#[inline]
+fn leading_u64(key: &[u8]) -> u64 {
+    let mut buf = [0u8; 8];
+    let n = key.len().min(8);
+    buf[..n].copy_from_slice(&key[..n]);
+    u64::from_be_bytes(buf)
+}
+
+/// Chop the interval range `[start, end)` into at most `parts` contiguous sub-ranges of
+/// roughly equal key-space width so the idle cores of a pool can scan one wide range in
+/// parallel. Split points are interpolated over the leading bytes of the range; any that do
+/// not fall strictly inside `(start, end)` are dropped, so a range too narrow to divide (or
+/// one with an unbounded end) degrades to a single sub-range. The sub-ranges tile the input
+/// without gaps or overlap.
+fn split_key_range(start: &[u8], end: &[u8], parts: usize) -> Vec<(Vec<u8>, Vec<u8>)> {
+    if parts <= 1 || end.is_empty() {
+        return vec![(start.to_vec(), end.to_vec())];
+    }
+    let (start_val, end_val) = (leading_u64(start), leading_u64(end));
+    if end_val <= start_val || end_val - start_val <= parts as u64 {
+        return vec![(start.to_vec(), end.to_vec())];
+    }
+    let step = (end_val - start_val) / parts as u64;
+
+    let mut bounds: Vec<Vec<u8>> = Vec::with_capacity(parts - 1);
+    for i in 1..parts as u64 {
+        let bound = (start_val + step * i).to_be_bytes().to_vec();
+        // Keep only boundaries strictly inside the range and strictly after the previous
+        // one, so the emitted sub-ranges stay non-empty and ordered.
+        if bound.as_slice() > start
+            && bound.as_slice() < end
+            && bounds.last().map_or(true, |prev| bound > *prev)
+        {
+            bounds.push(bound);
+        }
+    }
+
+    let mut ranges = Vec::with_capacity(bounds.len() + 1);
+    let mut prev = start.to_vec();
+    for bound in bounds {
+        ranges.push((prev.clone(), bound.clone()));
+        prev = bound;
+    }
+    ranges.push((prev, end.to_vec()));
+    ranges
+}
+
+/// Splits `ranges` into morsels, chopping each wide interval range into up to
+/// `max_splits_per_range` contiguous sub-ranges so that a single wide scan can be spread
+/// across idle workers. Point ranges (and ranges too narrow to divide) are emitted as-is.
+/// Each morsel keeps the index of the range it was derived from and a global ascending
+/// `order` so a downstream merge can restore the scan's key order deterministically.
+pub fn split_into_morsels(
+    ranges: &[coppb::KeyRange],
+    max_splits_per_range: usize,
+) -> Vec<Morsel> {
+    let mut morsels = Vec::with_capacity(ranges.len());
+    let mut order = 0;
+    for (range_index, range) in ranges.iter().enumerate() {
+        let sub_ranges = if is_point(range) {
+            vec![(range.get_start().to_vec(), range.get_end().to_vec())]
+        } else {
+            split_key_range(range.get_start(), range.get_end(), max_splits_per_range)
+        };
+        for (start, end) in sub_ranges {
+            let mut sub = coppb::KeyRange::new();
+            sub.set_start(start);
+            sub.set_end(end);
+            morsels.push(Morsel {
+                range_index,
+                order,
+                range: sub,
+            });
+            order += 1;
+        }
+    }
+    morsels
+}
+
+/// Accumulates partial responses from morsel subtasks and, once all have arrived, merges
+/// them into a single response preserving range order.
+struct MorselMerger {
+    partials: Vec<Option<coppb::Response>>,
+    metrics: ExecutorMetrics,
+    remaining: usize,
+    /// First error reported by any morsel, propagated to `callback` once every morsel has
+    /// reported a terminal result.
+    error: Option<Error>,
+    callback: Option<Callback>,
+}
+
+impl MorselMerger {
+    /// Record one morsel's terminal result. Every outcome — a partial response, an error
+    /// (`PoolBusy` when the tier is saturated, or a scan/region failure), or any other
+    /// value — decrements `remaining`, so the request always completes; the first error
+    /// is remembered and surfaced instead of a merged response.
+    fn collect(&mut self, order: usize, result: Result) {
+        match result {
+            Ok(Value::CoprocessorPartial(resp, metrics)) => {
+                self.partials[order] = Some(resp);
+                self.metrics.collect_metrics_into(&metrics);
+            }
+            // A morsel subtask only ever yields a partial response; ignore anything else.
+            Ok(_) => {}
+            Err(e) => if self.error.is_none() {
+                self.error = Some(e);
+            },
+        }
+        self.remaining -= 1;
+        if self.remaining == 0 {
+            if let Some(callback) = self.callback.take() {
+                match self.error.take() {
+                    Some(e) => callback(Err(e)),
+                    None => {
+                        let merged = self.merge();
+                        callback(Ok(Value::Coprocessor(merged)));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Concatenate the partial responses in ascending morsel order. The morsels tile the
+    /// scanned key space left to right, so stitching them back in `order` reproduces the
+    /// scan's key order — which is the order the fanned-out DAGs require.
+    ///
+    /// Morsel fan-out is only used for requests whose result order is the scan's key order
+    /// (or is order-insensitive). DAGs that impose their own ordering (TopN / order-by
+    /// expressions) are *not* split here — each partial would be an independent local
+    /// stream that concatenation cannot reassemble into a global ordering — and instead run
+    /// on the ordinary single-subtask path; see `async_execute_morsels`.
+    fn merge(&mut self) -> coppb::Response {
+        let mut merged = coppb::Response::new();
+        let mut data = Vec::new();
+        for partial in self.partials.drain(..) {
+            if let Some(resp) = partial {
+                data.extend_from_slice(resp.get_data());
+            }
+        }
+        merged.set_data(data);
+        merged
+    }
+}
+
+impl GrpcRequestWorker {
+    /// Fan a single coprocessor request out into many morsel subtasks. Each wide range is
+    /// chopped into contiguous sub-ranges so that idle workers in the owning pool can help
+    /// scan one range; the number of sub-ranges per range is capped by that pool's
+    /// concurrency, so the degree of parallelism never exceeds the threads available and
+    /// any excess morsels simply queue. Each morsel produces a partial response; a final
+    /// merge stitches them back in key order (see `MorselMerger::merge`) before invoking
+    /// `callback` once.
+    ///
+    /// Only order-insensitive or key-ordered DAGs may be routed here: a DAG that imposes
+    /// its own ordering (TopN / order-by expressions) must take the single-subtask path,
+    /// because the per-morsel partials are independent local streams that the concatenating
+    /// merge cannot reassemble into a global ordering.
+    pub fn async_execute_morsels(
+        &self,
+        ranges: Vec<coppb::KeyRange>,
+        priority: Priority,
+        morsel_row_limit: usize,
+        callback: Callback,
+    ) {
+        let max_splits_per_range = self.concurrency_for(priority).max(1);
+        let morsels = split_into_morsels(&ranges, max_splits_per_range);
+        if morsels.is_empty() {
+            // Nothing to scan: complete immediately rather than leaving the caller waiting.
+            callback(Ok(Value::Coprocessor(coppb::Response::new())));
+            return;
+        }
+        let merger = Arc::new(Mutex::new(MorselMerger {
+            partials: (0..morsels.len()).map(|_| None).collect(),
+            metrics: ExecutorMetrics::default(),
+            remaining: morsels.len(),
+            error: None,
+            callback: Some(callback),
+        }));
+
+        for morsel in morsels {
+            let merger = Arc::clone(&merger);
+            let order = morsel.order;
+            let subtask = CopSubTask::new(morsel.range, morsel_row_limit);
+            self.async_execute(
+                box subtask,
+                priority,
+                box move |result: Result| {
+                    merger.lock().unwrap().collect(order, result);
+                },
+            );
+        }
+    }
+}
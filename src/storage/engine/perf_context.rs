@@ -13,7 +13,64 @@
 
 use std::ops::{Deref, DerefMut};
 
-use rocksdb::PerfContext;
+use rocksdb::{self, PerfContext};
+
+/// The granularity at which RocksDB collects per-thread performance statistics, mirroring
+/// the discrete modes of the underlying rocksdb crate. Higher levels collect strictly more
+/// than lower ones; any field not collected under the active level is left at its default
+/// (`0`), so `PerfStatisticsInstant::delta` stays meaningful.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PerfLevel {
+    /// Collect nothing.
+    Disable,
+    /// Collect counters only, without timers.
+    EnableCount,
+    /// Collect counters and timers, except the mutex-wait timers.
+    EnableTimeExceptForMutex,
+    /// As above, additionally collecting CPU time (still excluding mutex wait).
+    EnableTimeAndCPUTimeExceptForMutex,
+    /// Collect everything, including mutex-wait time.
+    EnableTime,
+}
+
+impl From<PerfLevel> for rocksdb::PerfLevel {
+    fn from(level: PerfLevel) -> rocksdb::PerfLevel {
+        match level {
+            PerfLevel::Disable => rocksdb::PerfLevel::Disable,
+            PerfLevel::EnableCount => rocksdb::PerfLevel::EnableCount,
+            PerfLevel::EnableTimeExceptForMutex => rocksdb::PerfLevel::EnableTimeExceptForMutex,
+            PerfLevel::EnableTimeAndCPUTimeExceptForMutex => {
+                rocksdb::PerfLevel::EnableTimeAndCPUTimeExceptForMutex
+            }
+            PerfLevel::EnableTime => rocksdb::PerfLevel::EnableTime,
+        }
+    }
+}
+
+/// Set the perf level of the current thread's RocksDB `PerfContext`.
+pub fn set_perf_level(level: PerfLevel) {
+    rocksdb::set_perf_level(level.into());
+}
+
+/// An RAII guard that raises the thread-local perf level for the duration of a scope and
+/// resets it to `PerfLevel::Disable` on drop, so callers pay only for the statistics they
+/// request on the hot path.
+pub struct PerfStatisticsGuard {
+    _priv: (),
+}
+
+impl PerfStatisticsGuard {
+    pub fn new(level: PerfLevel) -> PerfStatisticsGuard {
+        set_perf_level(level);
+        PerfStatisticsGuard { _priv: () }
+    }
+}
+
+impl Drop for PerfStatisticsGuard {
+    fn drop(&mut self) {
+        set_perf_level(PerfLevel::Disable);
+    }
+}
 
 #[derive(Default, Debug, Clone, Copy, Add, AddAssign, Sub, SubAssign)]
 pub struct PerfStatisticsFields {
@@ -83,6 +140,20 @@ pub struct PerfStatisticsFields {
     pub env_lock_file_nanos: usize,
     pub env_unlock_file_nanos: usize,
     pub env_new_logger_nanos: usize,
+
+    // Per-thread file I/O accounting, sourced from RocksDB's `IOStatsContext`. Unlike
+    // `block_read_byte` (which counts cache-miss block reads) these report the raw device
+    // traffic that actually hit the underlying file descriptors.
+    pub bytes_read: usize,
+    pub bytes_written: usize,
+    pub open_nanos: usize,
+    pub allocate_nanos: usize,
+    pub write_nanos: usize,
+    pub read_nanos: usize,
+    pub range_sync_nanos: usize,
+    pub fsync_nanos: usize,
+    pub prepare_write_nanos: usize,
+    pub logger_nanos: usize,
 }
 
 /// Store statistics we need. Data comes from RocksDB's `PerfContext`.
@@ -92,8 +163,24 @@ pub struct PerfStatisticsInstant(pub PerfStatisticsFields);
 
 impl PerfStatisticsInstant {
     /// Create an instance which stores instant statistics values, retrieved at creation.
+    ///
+    /// This reads whatever the current thread's perf level is collecting; fields outside
+    /// that level are reported by RocksDB as `0`.
     pub fn new() -> Self {
+        Self::read()
+    }
+
+    /// Raise the thread-local perf level to `level`, then snapshot the statistics. Time
+    /// fields not collected under `level` remain at their default (`0`), keeping the
+    /// `Sub`-derived `delta` meaningful.
+    pub fn new_with_level(level: PerfLevel) -> Self {
+        set_perf_level(level);
+        Self::read()
+    }
+
+    fn read() -> Self {
         let perf_context = PerfContext::get();
+        let io_stats = rocksdb::IOStatsContext::get();
         PerfStatisticsInstant(PerfStatisticsFields {
             user_key_comparison_count: perf_context.user_key_comparison_count() as usize,
             block_cache_hit_count: perf_context.block_cache_hit_count() as usize,
@@ -168,6 +255,16 @@ impl PerfStatisticsInstant {
             env_lock_file_nanos: perf_context.env_lock_file_nanos() as usize,
             env_unlock_file_nanos: perf_context.env_unlock_file_nanos() as usize,
             env_new_logger_nanos: perf_context.env_new_logger_nanos() as usize,
+            bytes_read: io_stats.bytes_read() as usize,
+            bytes_written: io_stats.bytes_written() as usize,
+            open_nanos: io_stats.open_nanos() as usize,
+            allocate_nanos: io_stats.allocate_nanos() as usize,
+            write_nanos: io_stats.write_nanos() as usize,
+            read_nanos: io_stats.read_nanos() as usize,
+            range_sync_nanos: io_stats.range_sync_nanos() as usize,
+            fsync_nanos: io_stats.fsync_nanos() as usize,
+            prepare_write_nanos: io_stats.prepare_write_nanos() as usize,
+            logger_nanos: io_stats.logger_nanos() as usize,
         })
     }
 
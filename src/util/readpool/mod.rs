@@ -28,11 +28,23 @@ pub use self::priority::Priority;
 
 const TICK_INTERVAL_SEC: u64 = 1;
 
+quick_error! {
+    #[derive(Debug)]
+    pub enum Error {
+        Full {
+            description("read pool is full")
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct ReadPool {
     pool_high: FuturePool<Context>,
     pool_normal: FuturePool<Context>,
     pool_low: FuturePool<Context>,
+    max_tasks_high: usize,
+    max_tasks_normal: usize,
+    max_tasks_low: usize,
 }
 
 impl util::AssertSend for ReadPool {}
@@ -64,6 +76,9 @@ impl ReadPool {
                 tick_interval,
                 build_context_factory(),
             ),
+            max_tasks_high: config.max_tasks_high,
+            max_tasks_normal: config.max_tasks_normal,
+            max_tasks_low: config.max_tasks_low,
         }
     }
 
@@ -76,19 +91,37 @@ impl ReadPool {
         }
     }
 
+    #[inline]
+    fn get_max_tasks_by_priority(&self, priority: Priority) -> usize {
+        match priority {
+            Priority::High => self.max_tasks_high,
+            Priority::Normal => self.max_tasks_normal,
+            Priority::Low => self.max_tasks_low,
+        }
+    }
+
+    /// Spawn a future into the pool of the given priority, unless that pool already has
+    /// as many queued-plus-running tasks as its configured limit, in which case the
+    /// future is rejected with `Error::Full` so that the RPC layer can translate it into
+    /// a `ServerIsBusy` response and clients back off.
     pub fn future_execute<F>(
         &self,
         priority: Priority,
         future: F,
-    ) -> cpupool::CpuFuture<F::Item, F::Error>
+    ) -> Result<cpupool::CpuFuture<F::Item, F::Error>, Error>
     where
         F: Future + Send + 'static,
         F::Item: Send + 'static,
         F::Error: Send + 'static,
     {
-        // TODO: handle busy?
         let pool = self.get_pool_by_priority(priority);
-        pool.spawn(future)
+        let max_tasks = self.get_max_tasks_by_priority(priority);
+        let current_tasks = pool.get_running_task_count();
+        if current_tasks >= max_tasks {
+            Err(Error::Full)
+        } else {
+            Ok(pool.spawn(future))
+        }
     }
 }
 
@@ -130,6 +163,7 @@ mod tests {
                     Priority::High,
                     future::ok::<Vec<u8>, BoxError>(vec![1, 2, 4]),
                 )
+                .unwrap()
                 .wait(),
         );
 
@@ -140,7 +174,20 @@ mod tests {
                     Priority::High,
                     future::err::<(), BoxError>(box_err!("foobar")),
                 )
+                .unwrap()
                 .wait(),
         );
     }
+
+    #[test]
+    fn test_future_execute_busy() {
+        let mut config = Config::default();
+        config.max_tasks_high = 0;
+        let read_pool = ReadPool::new(&config);
+
+        match read_pool.future_execute(Priority::High, future::ok::<(), BoxError>(())) {
+            Err(Error::Full) => (),
+            _ => panic!("should be rejected with Error::Full"),
+        }
+    }
 }